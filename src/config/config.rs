@@ -1,8 +1,11 @@
 use std::fs;
 use std::error::Error;
 use std::marker::Sized;
+use std::path::Path;
 use serde_json::{Value, Map};
 use super::super::error::PafError;
+use super::sysconf::SystemConfig;
+use super::moduleconf::ModuleConfig;
 
 pub trait Configuration {
     fn read_from_file(path: &str) -> Result<Self, Box<Error>> where Self: Sized;
@@ -11,6 +14,282 @@ pub trait Configuration {
     fn as_map(&self) -> Map<String, Value>;
     fn as_json(&self) -> String;
     fn as_text(&self) -> String;
+
+    /// Returns every key under a dotted prefix with the prefix stripped. Both dotted keys
+    /// (e.g. `"http.port"`) and keys of a nested object under the prefix are included,
+    /// therefore, `subset("http")` turns `http.port` into `port`. Returns an empty map,
+    /// if there are no matching keys.
+    /// 
+    /// ## Arguments
+    /// * `prefix` - The key prefix without the trailing dot
+    /// 
+    /// ## Examples
+    /// ```
+    /// let config = GeneralConfig::read_config(r#"{"http.port": 80, "db": "x"}"#).unwrap();
+    /// let http = config.subset("http");
+    /// assert_eq!(http["port"], 80);
+    /// ```
+    fn subset(&self, prefix: &str) -> Map<String, Value> {
+        let dotted = format!("{}.", prefix);
+        let mut subset = Map::new();
+
+        for (k, v) in self.as_map() {
+            if let Some(stripped) = k.strip_prefix(&dotted) {
+                subset.insert(stripped.to_string(), v);
+            } else if k == prefix {
+                if let Value::Object(nested) = v {
+                    subset.extend(nested);
+                }
+            }
+        }
+        subset
+    }
+
+    /// Reads a JSON configuration string leniently. Comments (`//` and `/* */`) and
+    /// trailing commas before a closing `}` or `]` are removed, then the result is parsed
+    /// with `read_config`. If fails, raises an error.
+    /// 
+    /// ## Arguments
+    /// * `config` - A JSON object string with optional comments and trailing commas
+    /// 
+    /// ## Examples
+    /// ```
+    /// let json = r#"{
+    ///     // Hand-edited
+    ///     "c": [1, 2, 3,],
+    /// }"#;
+    /// let result = GeneralConfig::read_config_lenient(json).unwrap();
+    /// ```
+    fn read_config_lenient(config: &str) -> Result<Self, Box<Error>> where Self: Sized {
+        Self::read_config(&strip_lenient_json(config))
+    }
+
+    /// Flattens nested objects of the configuration into a single level map with dotted keys
+    /// (e.g. `{"http": {"port": 80}}` becomes `{"http.port": 80}`). Arrays are kept as values.
+    /// Raises an error, if objects are nested deeper than `MAX_DEPTH`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let config = GeneralConfig::read_config(r#"{"http": {"port": 80}}"#).unwrap();
+    /// assert_eq!(config.flatten().unwrap()["http.port"], 80);
+    /// ```
+    fn flatten(&self) -> Result<Map<String, Value>, Box<Error>> {
+        let mut flat = Map::new();
+        for (k, v) in self.as_map() {
+            _flatten_value(&k, v, 1, &mut flat)?;
+        }
+        Ok(flat)
+    }
+
+    /// Returns the dotted paths of every leaf key, which was not accessed. A leaf counts as
+    /// accessed, if its path, or the path of one of its parent objects is in the accessed list.
+    /// Objects nested deeper than `MAX_DEPTH` are treated as leaves. The result is sorted.
+    /// 
+    /// ## Arguments
+    /// * `accessed` - The dotted paths of the keys read by the module
+    /// 
+    /// ## Examples
+    /// ```
+    /// let config = GeneralConfig::read_config(r#"{"http": {"port": 80, "host": "x"}}"#).unwrap();
+    /// assert_eq!(config.unused_keys(&["http.port"]), vec!["http.host"]);
+    /// ```
+    fn unused_keys(&self, accessed: &[&str]) -> Vec<String> {
+        let mut leaves = Vec::new();
+        for (k, v) in self.as_map() {
+            _leaf_keys(&k, &v, 1, &mut leaves);
+        }
+
+        let mut unused: Vec<String> = leaves.into_iter()
+            .filter(|leaf| !accessed.iter().any(|a| leaf == a || leaf.starts_with(&format!("{}.", a))))
+            .collect();
+        unused.sort();
+        unused
+    }
+}
+
+/// Maximum depth of nested objects traversed by recursive configuration methods.
+pub const MAX_DEPTH: usize = 64;
+
+/// Utility function for recursively flattening a value under a dotted key.
+/// 
+/// ## Arguments
+/// * `key` - The dotted key of the value
+/// * `value` - The value to flatten
+/// * `depth` - The depth of the value
+/// * `flat` - The map collecting the flattened values
+fn _flatten_value(key: &str, value: Value, depth: usize, flat: &mut Map<String, Value>) -> Result<(), Box<Error>> {
+    if depth > MAX_DEPTH {
+        return Err(PafError::create_error(&format!("Configuration is nested deeper than {} levels at {}.", MAX_DEPTH, key)));
+    }
+
+    match value {
+        Value::Object(nested) => {
+            for (k, v) in nested {
+                _flatten_value(&format!("{}.{}", key, k), v, depth + 1, flat)?;
+            }
+        },
+        _ => {
+            flat.insert(key.to_string(), value);
+        }
+    }
+    Ok(())
+}
+
+/// Utility function for recursively collecting the dotted paths of leaf keys. Objects
+/// deeper than `MAX_DEPTH` are collected as leaves.
+/// 
+/// ## Arguments
+/// * `key` - The dotted key of the value
+/// * `value` - The value to traverse
+/// * `depth` - The depth of the value
+/// * `leaves` - The vector collecting the paths
+fn _leaf_keys(key: &str, value: &Value, depth: usize, leaves: &mut Vec<String>) {
+    match value {
+        Value::Object(nested) if depth < MAX_DEPTH && !nested.is_empty() => {
+            for (k, v) in nested {
+                _leaf_keys(&format!("{}.{}", key, k), v, depth + 1, leaves);
+            }
+        },
+        _ => leaves.push(key.to_string())
+    }
+}
+
+/// Removes comments (`//` and `/* */`) and trailing commas before a closing `}` or `]`
+/// from a JSON string. Characters inside strings are left untouched.
+/// 
+/// ## Arguments
+/// * `config` - A JSON string with optional comments and trailing commas
+/// 
+/// ## Examples
+/// ```
+/// assert_eq!(strip_lenient_json("[1, 2, /* three */ 3,]"), "[1, 2,  3]");
+/// ```
+pub fn strip_lenient_json(config: &str) -> String {
+    let chars: Vec<char> = config.chars().collect();
+    let mut stripped = String::with_capacity(config.len());
+    let mut in_string = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            stripped.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                stripped.push(chars[i + 1]);
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            continue;
+        } else if c == ',' && _closes_after_comma(&chars[i + 1..]) {
+            // Drop trailing comma
+        } else {
+            if c == '"' {
+                in_string = true;
+            }
+            stripped.push(c);
+        }
+        i += 1;
+    }
+    stripped
+}
+
+/// Checks if the next significant character (skipping whitespace and comments) closes an
+/// object or an array.
+fn _closes_after_comma(rest: &[char]) -> bool {
+    let mut i = 0;
+    while i < rest.len() {
+        if rest[i].is_whitespace() {
+            i += 1;
+        } else if rest[i] == '/' && rest.get(i + 1) == Some(&'/') {
+            while i < rest.len() && rest[i] != '\n' {
+                i += 1;
+            }
+        } else if rest[i] == '/' && rest.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < rest.len() && !(rest[i] == '*' && rest.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else {
+            return rest[i] == '}' || rest[i] == ']';
+        }
+    }
+    false
+}
+
+/// Reads a configuration file, and returns its content as a JSON string. The format
+/// is chosen by the extension of the file (`.json`, `.yaml`, `.yml`, or `.toml`).
+/// Files with unknown extensions are treated as JSON. If fails, raises an error.
+/// 
+/// ## Arguments
+/// * `path` - Path to the configuration file
+/// 
+/// ## Examples
+/// ```
+/// let json = read_file_as_json("config.yaml").unwrap();
+/// let res = GeneralConfig::read_config(&json).unwrap();
+/// ```
+pub fn read_file_as_json(path: &str) -> Result<String, Box<Error>> {
+    let config = fs::read_to_string(path)?;
+    let extension = Path::new(path).extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "yaml" | "yml" => {
+            let parsed: Value = serde_yaml::from_str(&config)
+                .map_err(|e| PafError::create_error(&format!("Could not parse YAML configuration {}: {}", path, e)))?;
+            Ok(parsed.to_string())
+        },
+        "toml" => {
+            let parsed: Value = toml::from_str(&config)
+                .map_err(|e| PafError::create_error(&format!("Could not parse TOML configuration {}: {}", path, e)))?;
+            Ok(parsed.to_string())
+        },
+        _ => Ok(config)
+    }
+}
+
+/// Enum for the configuration types, which can be loaded with `load`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ConfigKind {
+    General,
+    System,
+    Module
+}
+
+/// Reads a configuration file of any kind, and returns it as a boxed trait object, therefore,
+/// configurations can be handled generically. For supported formats, see `read_file_as_json`.
+/// If fails, raises the error of the underlying reader.
+/// 
+/// ## Arguments
+/// * `kind` - The type of the configuration
+/// * `path` - Path to the configuration file
+/// 
+/// ## Examples
+/// ```
+/// let config = load(ConfigKind::System, "config.json").unwrap();
+/// println!("{}", config.as_json());
+/// ```
+pub fn load(kind: ConfigKind, path: &str) -> Result<Box<dyn Configuration>, Box<Error>> {
+    let config: Box<dyn Configuration> = match kind {
+        ConfigKind::General => Box::new(GeneralConfig::read_from_file(path)?),
+        ConfigKind::System => Box::new(SystemConfig::read_from_file(path)?),
+        ConfigKind::Module => Box::new(ModuleConfig::read_from_file(path)?)
+    };
+    Ok(config)
 }
 
 /// A general configuration parser. Parses a single JSON object with KVP pairs.
@@ -20,8 +299,8 @@ pub struct GeneralConfig {
 }
 
 impl Configuration for GeneralConfig {
-    /// Reads a JSON configuration file, and create a `GeneralConfig` on
-    /// success. If fails, raises an error.
+    /// Reads a JSON, YAML, or TOML configuration file, and create a `GeneralConfig` on
+    /// success. If fails, raises an error. For supported formats, see `read_file_as_json`.
     /// 
     /// ## Arguments
     /// * `path` - Path to the configuration file
@@ -31,7 +310,7 @@ impl Configuration for GeneralConfig {
     /// let res = GeneralConfig::read_from_file("config.json").unwrap();
     /// ```
     fn read_from_file(path: &str) -> Result<GeneralConfig, Box<Error>> {
-        let config = fs::read_to_string(path)?;
+        let config = read_file_as_json(path)?;
         GeneralConfig::read_config(&config)
     }
 
@@ -79,6 +358,8 @@ impl Configuration for GeneralConfig {
 
     /// Serializes the unerlying configuration to whitespace delimited key-value pairs.
     /// If a value has depth > 1, serializes the value as a single line JSON string.
+    /// Strings, which are empty, contain line breaks or tabs, or have leading or trailing
+    /// whitespace are serialized as quoted JSON strings to keep them on a single line.
     /// 
     /// Using this method the following JSON configuration
     /// ```
@@ -98,11 +379,131 @@ impl Configuration for GeneralConfig {
         // Functional optimization of a simple for loop iterating over KVPs (k, v) in a HashMap and serializing them
         self.as_map().into_iter().fold(
             "".to_string(), |text, (k, v)|
-                text + k.as_str() + " " + v.as_str().unwrap_or(&serde_json::to_string(&v).unwrap_or("".to_string())) + "\n"
+                text + k.as_str() + " " + &_text_value(&v) + "\n"
+        ).trim().to_string()
+    }
+}
+
+impl GeneralConfig {
+    /// Converts every string value, which cleanly parses as a boolean (`"true"`, `"false"`),
+    /// an integer, or a finite float, into the corresponding JSON type. Nested objects and
+    /// arrays are converted recursively. Other strings, and values nested deeper than
+    /// `MAX_DEPTH` are left untouched.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let mut config = GeneralConfig::read_config(r#"{"a": "5", "b": "true", "c": "hello"}"#).unwrap();
+    /// config.coerce_scalars();
+    /// assert_eq!(config.as_map()["a"], 5);
+    /// ```
+    pub fn coerce_scalars(&mut self) {
+        self.config.values_mut().for_each(|v| _coerce_value(v, 1));
+    }
+
+    /// Serializes the underlying configuration to a pretty printed JSON with keys in
+    /// sorted order on every level. Unlike `as_json`, the output does not depend on the
+    /// internal ordering of the map. Objects nested deeper than `MAX_DEPTH` are kept as is.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let config = GeneralConfig::read_config(r#"{"b": 1, "a": {"d": 2, "c": 3}}"#).unwrap();
+    /// assert_eq!(config.as_json_sorted(), GeneralConfig::read_config(r#"{"a": {"c": 3, "d": 2}, "b": 1}"#).unwrap().as_json_sorted());
+    /// ```
+    pub fn as_json_sorted(&self) -> String {
+        serde_json::to_string_pretty(&_sorted_map(&self.config, 1)).unwrap()
+    }
+
+    /// Serializes the underlying configuration to whitespace delimited key-value pairs in
+    /// sorted key order. Nested values are serialized as single line JSON strings with their
+    /// keys sorted as well. See `as_text` for the format.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let config = GeneralConfig::read_config(r#"{"b": 5, "a": "x"}"#).unwrap();
+    /// assert_eq!(config.as_text_sorted(), "a x\nb 5");
+    /// ```
+    pub fn as_text_sorted(&self) -> String {
+        _sorted_map(&self.config, 1).into_iter().fold(
+            "".to_string(), |text, (k, v)|
+                text + k.as_str() + " " + &_text_value(&v) + "\n"
         ).trim().to_string()
     }
 }
 
+/// Private function for serializing a value for the text format. Simple strings are kept
+/// bare, while other values, and strings breaking the line-per-pair format or looking like
+/// JSON (e.g. `"5"` or `"[1]"`) are serialized as single line JSON, so they can be told apart.
+fn _text_value(value: &Value) -> String {
+    match value.as_str() {
+        Some(s) if !s.is_empty() && !s.contains(&['\n', '\r', '\t'][..]) && s.trim() == s
+            && !s.starts_with(&['"', '[', '{'][..]) && serde_json::from_str::<Value>(s).is_err() => s.to_string(),
+        _ => serde_json::to_string(value).unwrap_or_default()
+    }
+}
+
+/// Private function for converting string-encoded scalars in a value in place, recursing
+/// into nested objects and arrays. Values deeper than `MAX_DEPTH` are left untouched.
+fn _coerce_value(value: &mut Value, depth: usize) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+
+    match value {
+        Value::Object(obj) => obj.values_mut().for_each(|v| _coerce_value(v, depth + 1)),
+        Value::Array(arr) => arr.iter_mut().for_each(|v| _coerce_value(v, depth + 1)),
+        Value::String(s) => {
+            if let Some(coerced) = _parse_scalar(s) {
+                *value = coerced;
+            }
+        },
+        _ => ()
+    }
+}
+
+/// Private function for parsing a string as a boolean, an integer, or a finite float.
+/// Strings with a sign prefix or leading zeros (e.g. `"+5"`, `"007"`) are not considered
+/// numbers, as they are likely identifiers.
+fn _parse_scalar(s: &str) -> Option<Value> {
+    match s {
+        "true" => return Some(Value::Bool(true)),
+        "false" => return Some(Value::Bool(false)),
+        _ => ()
+    }
+
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    if !digits.starts_with(|c: char| c.is_ascii_digit())
+        || (digits.len() > 1 && digits.starts_with('0') && !digits[1..].starts_with('.')) {
+        return None;
+    }
+
+    if let Ok(int) = s.parse::<i64>() {
+        Some(Value::from(int))
+    } else if let Ok(uint) = s.parse::<u64>() {
+        Some(Value::from(uint))
+    } else {
+        s.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(Value::Number)
+    }
+}
+
+/// Private function for rebuilding a map with its keys inserted in sorted order, recursing
+/// into nested objects and arrays.
+fn _sorted_map(map: &Map<String, Value>, depth: usize) -> Map<String, Value> {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+
+    keys.into_iter().map(|k| (k.clone(), _sorted_value(&map[k], depth))).collect()
+}
+
+/// Private function for sorting the keys of every object in a value. Values deeper than
+/// `MAX_DEPTH` are cloned as is.
+fn _sorted_value(value: &Value, depth: usize) -> Value {
+    match value {
+        Value::Object(obj) if depth <= MAX_DEPTH => Value::Object(_sorted_map(obj, depth + 1)),
+        Value::Array(arr) if depth <= MAX_DEPTH => Value::Array(arr.iter().map(|v| _sorted_value(v, depth + 1)).collect()),
+        other => other.clone()
+    }
+}
+
 #[cfg(test)]
 mod test {
     mod read_from_file {
@@ -113,6 +514,42 @@ mod test {
             let res = GeneralConfig::read_from_file("test/config.json");
             assert!(res.is_ok());
         }
+
+        #[test]
+        fn reads_yaml_and_toml() {
+            let json = GeneralConfig::read_from_file("test/config.json").unwrap();
+            let yaml = GeneralConfig::read_from_file("test/config.yaml").unwrap();
+            let toml = GeneralConfig::read_from_file("test/config.toml").unwrap();
+            assert_eq!(json.as_map(), yaml.as_map());
+            assert_eq!(json.as_map(), toml.as_map());
+        }
+
+        #[test]
+        fn errs_on_invalid_toml() {
+            let err = GeneralConfig::read_from_file("test/config_broken.toml").err().unwrap();
+            assert!(err.to_string().contains("TOML"));
+        }
+    }
+
+    mod load {
+        use super::super::*;
+
+        #[test]
+        fn loads_every_kind() {
+            let general = load(ConfigKind::General, "test/config.json").unwrap();
+            let system = load(ConfigKind::System, "test/sysconfig_full.json").unwrap();
+            let module = load(ConfigKind::Module, "test/moduleconfig.json").unwrap();
+
+            assert_eq!(general.as_json(), GeneralConfig::read_from_file("test/config.json").unwrap().as_json());
+            assert_eq!(system.as_json(), SystemConfig::read_from_file("test/sysconfig_full.json").unwrap().as_json());
+            assert_eq!(module.as_json(), ModuleConfig::read_from_file("test/moduleconfig.json").unwrap().as_json());
+        }
+
+        #[test]
+        fn throws_error_on_invalid_file() {
+            assert!(load(ConfigKind::General, "test/missing.json").is_err());
+            assert!(load(ConfigKind::Module, "test/config_broken.toml").is_err());
+        }
     }
 
     mod read_config {
@@ -195,5 +632,328 @@ mod test {
             let text = config.as_text();
             assert_eq!(text, expected);
         }
+
+        #[test]
+        fn keeps_plain_strings_bare() {
+            let config = GeneralConfig::read_config(r#"{"a": "John Doe"}"#).unwrap();
+            assert_eq!(config.as_text(), "a John Doe");
+        }
+
+        #[test]
+        fn quotes_multiline_strings() {
+            let config = GeneralConfig::read_config(r#"{"a": "first\nsecond", "b": "x"}"#).unwrap();
+            let text = config.as_text();
+            assert_eq!(text, "a \"first\\nsecond\"\nb x");
+            assert_eq!(text.lines().count(), 2);
+
+            let quoted = text.lines().next().unwrap().split_once(' ').unwrap().1;
+            assert_eq!(serde_json::from_str::<Value>(quoted).unwrap(), "first\nsecond");
+        }
+
+        #[test]
+        fn quotes_whitespace_strings() {
+            let config = GeneralConfig::read_config(r#"{"a": " padded ", "b": "tab\tbed", "c": ""}"#).unwrap();
+            assert_eq!(config.as_text(), "a \" padded \"\nb \"tab\\tbed\"\nc \"\"");
+        }
+
+        #[test]
+        fn quotes_strings_starting_with_quote() {
+            let config = GeneralConfig::read_config(r#"{"a": "\"hi\"", "b": "\"open"}"#).unwrap();
+            let text = config.as_text();
+            assert_eq!(text, "a \"\\\"hi\\\"\"\nb \"\\\"open\"");
+
+            let quoted = text.lines().next().unwrap().split_once(' ').unwrap().1;
+            assert_eq!(serde_json::from_str::<Value>(quoted).unwrap(), "\"hi\"");
+        }
+
+        #[test]
+        fn quotes_json_like_strings() {
+            let config = GeneralConfig::read_config(r#"{"a": "[1]", "b": "5", "c": "true", "d": "{x", "e": 5}"#).unwrap();
+            assert_eq!(config.as_text_sorted(), "a \"[1]\"\nb \"5\"\nc \"true\"\nd \"{x\"\ne 5");
+        }
+    }
+
+    mod coerce_scalars {
+        use super::super::*;
+
+        #[test]
+        fn converts_scalars() {
+            let mut config = GeneralConfig::read_config(r#"{
+                "a": "5",
+                "b": "true",
+                "c": "-2.5",
+                "g": "0.5",
+                "d": {"e": ["false", "18446744073709551615", 3]}
+            }"#).unwrap();
+            config.coerce_scalars();
+
+            let map = config.as_map();
+            assert_eq!(map["a"], 5);
+            assert_eq!(map["b"], true);
+            assert_eq!(map["c"], -2.5);
+            assert_eq!(map["g"], 0.5);
+            assert_eq!(map["d"]["e"][0], false);
+            assert_eq!(map["d"]["e"][1], u64::MAX);
+            assert_eq!(map["d"]["e"][2], 3);
+        }
+
+        #[test]
+        fn leaves_strings_untouched() {
+            let mut config = GeneralConfig::read_config(r#"{
+                "a": "hello",
+                "b": "007",
+                "c": "+5",
+                "d": "NaN",
+                "e": " 5",
+                "f": "True"
+            }"#).unwrap();
+            config.coerce_scalars();
+
+            let map = config.as_map();
+            assert_eq!(map["a"], "hello");
+            assert_eq!(map["b"], "007");
+            assert_eq!(map["c"], "+5");
+            assert_eq!(map["d"], "NaN");
+            assert_eq!(map["e"], " 5");
+            assert_eq!(map["f"], "True");
+        }
+
+        #[test]
+        fn stops_at_max_depth() {
+            let json = format!("{}\"5\"{}", "{\"a\": ".repeat(MAX_DEPTH), "}".repeat(MAX_DEPTH));
+            let mut config = GeneralConfig::read_config(&json).unwrap();
+            config.coerce_scalars();
+            assert!(config.as_json().contains(": 5"));
+
+            let json = format!("{}\"5\"{}", "{\"a\": ".repeat(MAX_DEPTH + 1), "}".repeat(MAX_DEPTH + 1));
+            let mut config = GeneralConfig::read_config(&json).unwrap();
+            config.coerce_scalars();
+            assert!(config.as_json().contains(": \"5\""));
+        }
+    }
+
+    mod sorted_output {
+        use super::super::*;
+
+        #[test]
+        fn is_independent_of_insertion_order() {
+            let first = GeneralConfig::read_config(r#"{"b": 5, "a": {"y": [{"q": 1, "p": 2}], "x": "z"}, "c": true}"#).unwrap();
+            let second = GeneralConfig::read_config(r#"{"c": true, "a": {"x": "z", "y": [{"p": 2, "q": 1}]}, "b": 5}"#).unwrap();
+            assert_eq!(first.as_json_sorted(), second.as_json_sorted());
+            assert_eq!(first.as_text_sorted(), second.as_text_sorted());
+        }
+
+        #[test]
+        fn emits_keys_in_sorted_order() {
+            let config = GeneralConfig::read_config(r#"{"b": 5, "a": {"y": 1, "x": 2}}"#).unwrap();
+            assert_eq!(config.as_text_sorted(), "a {\"x\":2,\"y\":1}\nb 5");
+        }
+
+        #[test]
+        fn keeps_objects_past_max_depth() {
+            let json = format!("{}{{\"b\": 1, \"a\": 2}}{}", "{\"a\": ".repeat(MAX_DEPTH + 1), "}".repeat(MAX_DEPTH + 1));
+            let config = GeneralConfig::read_config(&json).unwrap();
+            assert_eq!(config.as_json_sorted(), config.as_json());
+        }
+    }
+
+    mod subset {
+        use super::super::*;
+
+        #[test]
+        fn returns_matching_keys() {
+            let json = r#"{
+                "http.port": 8080,
+                "http.host": "localhost",
+                "https.port": 443,
+                "db": "openpaf"
+            }"#;
+            let config = GeneralConfig::read_config(json).unwrap();
+            let subset = config.subset("http");
+            assert_eq!(subset.len(), 2);
+            assert_eq!(subset["port"], 8080);
+            assert_eq!(subset["host"], "localhost");
+        }
+
+        #[test]
+        fn returns_nested_keys() {
+            let json = r#"{
+                "http": {
+                    "port": 8080
+                },
+                "http.host": "localhost"
+            }"#;
+            let config = GeneralConfig::read_config(json).unwrap();
+            let subset = config.subset("http");
+            assert_eq!(subset.len(), 2);
+            assert_eq!(subset["port"], 8080);
+            assert_eq!(subset["host"], "localhost");
+        }
+
+        #[test]
+        fn returns_empty_map_without_matches() {
+            let json = r#"{
+                "https.port": 443,
+                "http": 80
+            }"#;
+            let config = GeneralConfig::read_config(json).unwrap();
+            assert!(config.subset("http").is_empty());
+        }
     }
-}
\ No newline at end of file
+
+    mod read_config_lenient {
+        use super::super::*;
+
+        #[test]
+        fn accepts_trailing_comma_in_object() {
+            let json = r#"{
+                "a": "b",
+                "b": 5,
+            }"#;
+            assert!(GeneralConfig::read_config(json).is_err());
+            let config = GeneralConfig::read_config_lenient(json).unwrap();
+            assert_eq!(config.config["b"], 5);
+        }
+
+        #[test]
+        fn accepts_trailing_comma_in_array() {
+            let json = r#"{
+                "c": [1, 2, 3,
+                ]
+            }"#;
+            let config = GeneralConfig::read_config_lenient(json).unwrap();
+            assert_eq!(config.config["c"].as_array().unwrap().to_vec(), vec!(1, 2, 3));
+        }
+
+        #[test]
+        fn accepts_comments() {
+            let json = r#"{
+                // Line comment
+                "a": "b", /* Block
+                comment */
+                "c": [1, 2, 3 /* three */, ] // Trailing comma with comment
+            }"#;
+            let config = GeneralConfig::read_config_lenient(json).unwrap();
+            assert_eq!(config.config["a"], "b");
+            assert_eq!(config.config["c"].as_array().unwrap().len(), 3);
+        }
+
+        #[test]
+        fn keeps_strings_intact() {
+            let json = r#"{
+                "a": "b,}",
+                "url": "http://localhost/*path*/",
+                "quoted": "say \"hi,]\"",
+            }"#;
+            let config = GeneralConfig::read_config_lenient(json).unwrap();
+            assert_eq!(config.config["a"], "b,}");
+            assert_eq!(config.config["url"], "http://localhost/*path*/");
+            assert_eq!(config.config["quoted"], "say \"hi,]\"");
+        }
+
+        #[test]
+        fn works_for_typed_configs() {
+            let json = r#"{
+                "modules": [{
+                    "name": "dummy",
+                    "mod_type": "Analysis",
+                },],
+            }"#;
+            assert!(super::super::super::sysconf::SystemConfig::read_config_lenient(json).is_ok());
+        }
+    }
+
+    mod flatten {
+        use super::super::*;
+
+        #[test]
+        fn flattens_nested_objects() {
+            let json = r#"{
+                "http": {
+                    "port": 80,
+                    "tls": {
+                        "enabled": false
+                    }
+                },
+                "c": [1, 2, 3]
+            }"#;
+            let flat = GeneralConfig::read_config(json).unwrap().flatten().unwrap();
+            assert_eq!(flat.len(), 3);
+            assert_eq!(flat["http.port"], 80);
+            assert_eq!(flat["http.tls.enabled"], false);
+            assert_eq!(flat["c"].as_array().unwrap().len(), 3);
+        }
+
+        #[test]
+        fn accepts_depth_limit() {
+            let json = format!("{}1{}", "{\"a\": ".repeat(MAX_DEPTH), "}".repeat(MAX_DEPTH));
+            let config = GeneralConfig::read_config(&json).unwrap();
+            let flat = config.flatten().unwrap();
+            assert_eq!(flat.len(), 1);
+            assert_eq!(flat.keys().next().unwrap().split('.').count(), MAX_DEPTH);
+        }
+
+        #[test]
+        fn errs_beyond_depth_limit() {
+            let json = format!("{}1{}", "{\"a\": ".repeat(MAX_DEPTH + 1), "}".repeat(MAX_DEPTH + 1));
+            let config = GeneralConfig::read_config(&json).unwrap();
+            let err = config.flatten().err().unwrap();
+            assert!(err.to_string().contains("nested deeper"));
+        }
+    }
+
+    mod unused_keys {
+        use super::super::*;
+
+        #[test]
+        fn reports_remaining_keys() {
+            let config = GeneralConfig::read_config(r#"{
+                "http": {
+                    "port": 80,
+                    "tls": {
+                        "enabled": false,
+                        "cert": "cert.pem"
+                    }
+                },
+                "name": "module",
+                "c": [1, 2, 3]
+            }"#).unwrap();
+            let unused = config.unused_keys(&["name", "http.tls.enabled"]);
+            assert_eq!(unused, vec!["c", "http.port", "http.tls.cert"]);
+        }
+
+        #[test]
+        fn treats_parent_as_accessed_subtree() {
+            let config = GeneralConfig::read_config(r#"{
+                "http": {
+                    "port": 80,
+                    "tls": {
+                        "enabled": false,
+                        "cert": "cert.pem"
+                    }
+                },
+                "name": "module",
+                "c": [1, 2, 3]
+            }"#).unwrap();
+            assert_eq!(config.unused_keys(&["http.tls", "c"]), vec!["http.port", "name"]);
+            assert!(config.unused_keys(&["http", "name", "c"]).is_empty());
+        }
+
+        #[test]
+        fn does_not_match_partial_segments() {
+            let config = GeneralConfig::read_config(r#"{
+                "http": {
+                    "port": 80,
+                    "tls": {
+                        "enabled": false,
+                        "cert": "cert.pem"
+                    }
+                },
+                "name": "module",
+                "c": [1, 2, 3]
+            }"#).unwrap();
+            let unused = config.unused_keys(&["http.tl", "nam"]);
+            assert_eq!(unused.len(), 5);
+        }
+    }
+}
@@ -1,8 +1,16 @@
 use std::error::Error;
 use std::fmt;
 
+/// Enum for the broad categories of errors raised by OpenPAF.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorKind {
+    General,
+    Database
+}
+
 pub struct PafError {
-    pub message: String
+    pub message: String,
+    pub kind: ErrorKind
 }
 
 impl Error for PafError {}
@@ -21,6 +29,10 @@ impl fmt::Debug for PafError {
 
 impl PafError {
     pub fn create_error(message: &str) -> Box<PafError> {
-        Box::new(PafError{message: String::from(message)})
+        PafError::create_error_with_kind(ErrorKind::General, message)
+    }
+
+    pub fn create_error_with_kind(kind: ErrorKind, message: &str) -> Box<PafError> {
+        Box::new(PafError{message: String::from(message), kind})
     }
 }
@@ -1,18 +1,39 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::time::datetime::DateTime;
+use super::error::PafError;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
 pub enum ModuleType {
     Input,
     Analysis,
     Output
 }
 
-#[derive(Deserialize, Serialize)]
+impl fmt::Display for ModuleType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ModuleType::Input => "Input",
+            ModuleType::Analysis => "Analysis",
+            ModuleType::Output => "Output"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
 pub struct Module {
     pub name: String,
     pub path: Option<String>,
     pub config: Option<String>,
-    pub mod_type: ModuleType
+    pub mod_type: ModuleType,
+    pub schedule: Option<String>,
+    pub env: Option<BTreeMap<String, String>>,
+    pub last_run: Option<String>,
+    pub required_keys: Option<Vec<String>>
 }
 
 /// A default dummy module for system config.
@@ -22,7 +43,179 @@ impl Default for Module {
             name: "dummy".to_string(),
             path: None,
             config: None,
-            mod_type: ModuleType::Analysis
+            mod_type: ModuleType::Analysis,
+            schedule: None,
+            env: None,
+            last_run: None,
+            required_keys: None
+        }
+    }
+}
+
+impl Module {
+    /// Returns the environment variables to set when launching the module as key-value
+    /// pairs sorted by key. Returns an empty vector, if there are none configured.
+    /// 
+    /// ## Examples
+    /// ```
+    /// for (key, value) in module.env_vars() {
+    ///     command.env(key, value);
+    /// }
+    /// ```
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        self.env.as_ref()
+            .map(|env| env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+            .unwrap_or_default()
+    }
+
+    /// Checks if the module has the type expected by a pipeline slot, and raises an error
+    /// naming the module and both types otherwise.
+    /// 
+    /// ## Arguments
+    /// * `expected` - The type of the pipeline slot
+    /// 
+    /// ## Examples
+    /// ```
+    /// module.expect_type(&ModuleType::Input)?;
+    /// ```
+    pub fn expect_type(&self, expected: &ModuleType) -> Result<(), Box<Error>> {
+        if &self.mod_type == expected {
+            Ok(())
+        } else {
+            Err(PafError::create_error(&format!("Module {} has type {}, but {} was expected.",
+                self.name, self.mod_type, expected)))
+        }
+    }
+
+    /// Returns the time of the last run of the module. Returns `None`, if the module
+    /// did not run yet, or the stored time is not a valid UTC timestamp.
+    pub fn last_run_datetime(&self) -> Option<DateTime> {
+        self.last_run.as_ref().and_then(|ts| DateTime::from_json_value(&Value::String(ts.to_string())).ok())
+    }
+
+    /// Stores the time of the last run of the module as a UTC timestamp. The timestamp is
+    /// serialized like JSON timestamps, therefore, it does not depend on the default format.
+    /// 
+    /// ## Arguments
+    /// * `dt` - The time of the last run
+    pub fn set_last_run(&mut self, dt: &DateTime) {
+        self.last_run = dt.to_json_value().as_str().map(|ts| ts.to_string());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    mod eq {
+        use super::super::*;
+
+        #[test]
+        fn compares_by_value() {
+            let module = Module {
+                name: "dummy".to_string(),
+                path: Some("dummy".to_string()),
+                config: None,
+                mod_type: ModuleType::Input,
+                schedule: None,
+                env: None,
+                last_run: None,
+                required_keys: None
+            };
+            let same = module.clone();
+            let other_path = Module {path: None, ..module.clone()};
+            let other_type = Module {mod_type: ModuleType::Output, ..module.clone()};
+
+            assert!(module == same);
+            assert!(module != other_path);
+            assert!(module != other_type);
+        }
+    }
+
+    mod env_vars {
+        use super::super::*;
+
+        #[test]
+        fn returns_configured_pairs() {
+            let module: Module = serde_json::from_str(r#"{
+                "name": "dummy",
+                "mod_type": "Input",
+                "env": {"LANG": "C", "DEBUG": "1"}
+            }"#).unwrap();
+            assert_eq!(module.env_vars(), vec![
+                ("DEBUG".to_string(), "1".to_string()),
+                ("LANG".to_string(), "C".to_string())
+            ]);
+        }
+
+        #[test]
+        fn returns_empty_without_env() {
+            let module: Module = serde_json::from_str(r#"{
+                "name": "dummy",
+                "mod_type": "Input"
+            }"#).unwrap();
+            assert!(module.env.is_none());
+            assert!(module.env_vars().is_empty());
+        }
+    }
+
+    mod last_run {
+        use super::super::*;
+
+        #[test]
+        fn round_trips_through_serialization() {
+            let mut module = Module::default();
+            let dt = DateTime::from_timestamp("2019-07-26 12:30:15", None).unwrap();
+            module.set_last_run(&dt);
+
+            let json = serde_json::to_string(&module).unwrap();
+            let parsed: Module = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.last_run, Some("2019-07-26 12:30:15".to_string()));
+            assert_eq!(parsed.last_run_datetime().unwrap().to_epoch(), dt.to_epoch());
+        }
+
+        #[test]
+        fn ignores_default_format() {
+            let mut module = Module::default();
+            let dt = DateTime::from_epoch(1_500_000_000);
+
+            DateTime::set_default_format("%Y-%m-%dT%H:%M:%S").unwrap();
+            module.set_last_run(&dt);
+            DateTime::reset_default_format();
+            assert_eq!(module.last_run, Some("2017-07-14 02:40:00".to_string()));
+            assert_eq!(module.last_run_datetime().unwrap().to_epoch(), dt.to_epoch());
+
+            DateTime::set_default_format("%Y-%m-%dT%H:%M:%S").unwrap();
+            let parsed = module.last_run_datetime().map(|dt| dt.to_epoch());
+            DateTime::reset_default_format();
+            assert_eq!(parsed, Some(dt.to_epoch()));
+        }
+
+        #[test]
+        fn returns_none_without_last_run() {
+            let module: Module = serde_json::from_str(r#"{
+                "name": "dummy",
+                "mod_type": "Input"
+            }"#).unwrap();
+            assert!(module.last_run_datetime().is_none());
+
+            let invalid = Module {last_run: Some("yesterday".to_string()), ..module};
+            assert!(invalid.last_run_datetime().is_none());
         }
     }
-}
\ No newline at end of file
+
+    mod expect_type {
+        use super::super::*;
+
+        #[test]
+        fn accepts_matching_type() {
+            let module = Module {mod_type: ModuleType::Input, ..Default::default()};
+            assert!(module.expect_type(&ModuleType::Input).is_ok());
+        }
+
+        #[test]
+        fn rejects_mismatching_type() {
+            let module = Module {mod_type: ModuleType::Output, ..Default::default()};
+            let err = module.expect_type(&ModuleType::Input).err().unwrap();
+            assert_eq!(err.to_string(), "Module dummy has type Output, but Input was expected.");
+        }
+    }
+}
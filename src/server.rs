@@ -1,35 +1,66 @@
+use std::env;
 use std::error::Error;
+use std::net::{IpAddr, Ipv6Addr, TcpStream, ToSocketAddrs};
+#[cfg(feature = "ssh")]
+use std::path::Path;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use machine_ip;
 use whoami;
 use super::error::PafError;
 
+/// Environment variable for pinning the current machine's IP, if autodetection fails.
+const MACHINE_IP_VAR: &str = "OPENPAF_MACHINE_IP";
+
+/// Environment variable for overriding the default SSH port of servers.
+const DEFAULT_SSH_PORT_VAR: &str = "OPENPAF_DEFAULT_SSH_PORT";
+
 /// Struct representing individual servers in a server chain.
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Default)]
 pub struct Server {
     name: Option<String>,
     ip: String,
     ssh_port: Option<u32>,
-    user: Option<String>
+    user: Option<String>,
+    identity_file: Option<String>,
+    // Never written back, so serialized configurations do not leak it
+    #[serde(skip_serializing)]
+    #[cfg_attr(not(feature = "ssh"), allow(dead_code))]
+    password: Option<String>
+}
+
+/// Servers are identified by their IP and SSH port. A missing port is treated as port 22.
+impl PartialEq for Server {
+    fn eq(&self, other: &Server) -> bool {
+        self.ip == other.ip && self.ssh_port_or(22) == other.ssh_port_or(22)
+    }
 }
 
 impl Server {
-    /// Sorts a list of server objects in place.
+    /// Sorts a list of server objects in place by their sort keys. Servers with equal
+    /// keys are ordered by their IP strings.
     /// 
     /// ## Arguments
     /// * `servers` - array of servers
     fn _sort(servers: &mut Vec<Server>) {
-        servers.sort_by(|a, b| a.ip.cmp(&b.ip))
+        servers.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()).then_with(|| a.ip.cmp(&b.ip)))
     }
 
     /// Gets the current machine's IP, if no argument is provided.
     /// Returns the argument otherwise.
     /// 
+    /// The IP is resolved with the following precedence:
+    /// 1. The `ip` argument, if provided.
+    /// 2. The `OPENPAF_MACHINE_IP` environment variable, if set and not empty.
+    /// 3. The autodetected IP of the current machine.
+    /// 
     /// ## Arguments
     /// * `ip` - an optional IP
     fn _get_ip(ip: Option<String>) -> Option<String> {
         if let Some(input) = ip {
             Some(input)
+        } else if let Some(env_ip) = env::var(MACHINE_IP_VAR).ok().filter(|e| !e.trim().is_empty()) {
+            Some(env_ip.trim().to_string())
         } else {
             if let Some(curr_ip) = machine_ip::get() {
                 Some(curr_ip.to_string())
@@ -45,7 +76,9 @@ impl Server {
             name: name,
             ip: ip,
             ssh_port: ssh_port,
-            user: user
+            user: user,
+            identity_file: None,
+            password: None
         }
     }
 
@@ -70,6 +103,42 @@ impl Server {
     /// ```
     pub fn next_server(servers: &mut Vec<Server>, ip: Option<String>) -> Result<&Server, Box<Error>> {
         Server::_sort(servers);
+        Server::_next_in_ring(servers, ip)
+    }
+
+    /// Finds the next server in an unordered array of servers treated as a ring starting
+    /// from a designated head server. Sorts the array, rotates it, so the head comes first,
+    /// then identifies the provided IP (or the current machine's IP), and returns the next
+    /// `Server` in the list. The array is left in ring order. Returns an error, if the head
+    /// or the server cannot be found in the list.
+    /// 
+    /// ## Arguments
+    /// * `servers` - array of servers
+    /// * `ip` - an optional IP string
+    /// * `head_ip` - the IP of the head server
+    /// 
+    /// ## Examples
+    /// ```
+    /// let mut servers = vec![
+    ///     Server {ip: "172.16.5.251".to_string(), ssh_port: None, name: None},
+    ///     Server {ip: "172.16.5.250".to_string(), ssh_port: None, name: None},
+    ///     Server {ip: "172.11.3.110".to_string(), ssh_port: None, name: None}
+    /// ];
+    /// let next = Server::next_server_from_head(&mut servers, Some("172.11.3.110".to_string()), "172.16.5.250").unwrap();
+    /// assert_eq!(next.ip, "172.16.5.250");
+    /// assert_eq!(servers[0].ip, "172.16.5.250");
+    /// ```
+    pub fn next_server_from_head<'a>(servers: &'a mut Vec<Server>, ip: Option<String>, head_ip: &str) -> Result<&'a Server, Box<Error>> {
+        Server::_sort(servers);
+        let head = servers.iter().position(|e| e.ip == head_ip)
+            .ok_or_else(|| PafError::create_error(&format!("Could not find head server {} in the server list.", head_ip)))?;
+        servers.rotate_left(head);
+        Server::_next_in_ring(servers, ip)
+    }
+
+    /// Private method for finding the server following the provided IP (or the current machine's
+    /// IP) in an ordered list. The first server follows the last one.
+    fn _next_in_ring(servers: &[Server], ip: Option<String>) -> Result<&Server, Box<Error>> {
         if let Some(needle) = Server::_get_ip(ip) {
             if let Some(i) = servers.iter().position(|e| e.ip == needle) {
                 if i == servers.len() - 1 {
@@ -81,7 +150,7 @@ impl Server {
                 Err(PafError::create_error("Could not find current machine's IP in the server list."))
             }
         } else {
-            Err(PafError::create_error("Unable to extract current machine's IP."))
+            Err(PafError::create_error(&format!("Unable to extract current machine's IP. Set {} to provide it explicitly.", MACHINE_IP_VAR)))
         }
     }
 
@@ -107,6 +176,78 @@ impl Server {
         servers.dedup_by(|a, b| a.ip == b.ip);
     }
 
+    /// Renders a server chain as a human readable list. Sorts the array, and prints every
+    /// server in order as `-> name (ip:port)`. The provided IP (or the current machine's IP)
+    /// is marked with a trailing `*`.
+    /// 
+    /// ## Arguments
+    /// * `servers` - array of servers
+    /// * `current_ip` - an optional IP string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let mut servers = vec![
+    ///     Server::new(Some("second".to_string()), "172.16.5.251".to_string(), None, None),
+    ///     Server::new(Some("first".to_string()), "172.16.5.250".to_string(), Some(2222), None)
+    /// ];
+    /// let chain = Server::render_chain(&mut servers, Some("172.16.5.250".to_string()));
+    /// assert_eq!(chain, "-> first (172.16.5.250:2222) *\n-> second (172.16.5.251:22)");
+    /// ```
+    pub fn render_chain(servers: &mut Vec<Server>, current_ip: Option<String>) -> String {
+        Server::_sort(servers);
+        let current = Server::_get_ip(current_ip);
+
+        servers.iter().map(|server| {
+            let name = server.name();
+            let label = if name.is_empty() { String::new() } else { format!("{} ", name) };
+            let marker = if current.as_ref() == Some(&server.ip) { " *" } else { "" };
+            format!("-> {}({}:{}){}", label, server.ip, server.ssh_port(), marker)
+        }).collect::<Vec<String>>().join("\n")
+    }
+
+    /// Compares two server chains, and returns the servers added to and removed from
+    /// the old chain as `(added, removed)`. Servers are identified by their IP and SSH port.
+    /// Both lists are sorted by IP.
+    /// 
+    /// ## Arguments
+    /// * `old` - the previous server chain
+    /// * `new` - the current server chain
+    /// 
+    /// ## Examples
+    /// ```
+    /// let old = vec![Server::new(None, "172.16.5.250".to_string(), None, None)];
+    /// let new = vec![Server::new(None, "172.16.5.251".to_string(), None, None)];
+    /// let (added, removed) = Server::diff(&old, &new);
+    /// assert_eq!(added[0].ip(), "172.16.5.251");
+    /// assert_eq!(removed[0].ip(), "172.16.5.250");
+    /// ```
+    pub fn diff(old: &[Server], new: &[Server]) -> (Vec<Server>, Vec<Server>) {
+        let mut added: Vec<Server> = new.iter().filter(|s| !old.contains(s)).cloned().collect();
+        let mut removed: Vec<Server> = old.iter().filter(|s| !new.contains(s)).cloned().collect();
+        Server::_sort(&mut added);
+        Server::_sort(&mut removed);
+
+        (added, removed)
+    }
+
+    /// Probes the SSH port of every server in a chain, and returns the IP of each server with
+    /// its reachability (see `is_reachable`). Every server is probed, even if some of them are
+    /// unreachable, therefore, the result is a full connectivity report in the input order.
+    /// 
+    /// ## Arguments
+    /// * `servers` - array of servers
+    /// * `timeout_ms` - Timeout for connecting to a single server in milliseconds
+    /// 
+    /// ## Examples
+    /// ```
+    /// for (ip, reachable) in Server::check_chain(&servers, 1000) {
+    ///     println!("{}: {}", ip, if reachable {"up"} else {"down"});
+    /// }
+    /// ```
+    pub fn check_chain(servers: &[Server], timeout_ms: u64) -> Vec<(String, bool)> {
+        servers.iter().map(|server| (server.ip(), server.is_reachable(timeout_ms))).collect()
+    }
+
     /// Returns the name of the server. If there is none,
     /// returns an empty string.
     pub fn name(&self) -> String {
@@ -122,10 +263,41 @@ impl Server {
         self.ip.to_string()
     }
 
-    /// Returns the SSH port of the server. If there is none,
-    /// returns the default port 22.
+    /// Returns a key for ordering servers numerically by IP, then by SSH port. IPv4
+    /// addresses come before IPv6 ones. If the IP cannot be parsed (e.g. it is a host
+    /// name), the largest IPv6 address is used, so such servers are ordered last.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let server = Server::new(None, "172.16.5.9".to_string(), Some(2222), None);
+    /// assert_eq!(server.sort_key(), ("172.16.5.9".parse().unwrap(), 2222));
+    /// ```
+    pub fn sort_key(&self) -> (IpAddr, u32) {
+        let ip = self.ip.trim().parse::<IpAddr>()
+            .unwrap_or(IpAddr::V6(Ipv6Addr::from(u128::MAX)));
+        (ip, self.ssh_port())
+    }
+
+    /// Returns the SSH port of the server.
+    /// 
+    /// The port is resolved with the following precedence:
+    /// 1. The port of the server, if configured.
+    /// 2. The `OPENPAF_DEFAULT_SSH_PORT` environment variable, if set to a valid port.
+    /// 3. The default port 22.
     pub fn ssh_port(&self) -> u32 {
-        self.ssh_port.unwrap_or(22)
+        let default = env::var(DEFAULT_SSH_PORT_VAR).ok()
+            .and_then(|p| p.trim().parse::<u32>().ok())
+            .unwrap_or(22);
+        self.ssh_port_or(default)
+    }
+
+    /// Returns the SSH port of the server. If there is none,
+    /// returns the provided default port.
+    /// 
+    /// ## Arguments
+    /// * `default` - the port to use, if the server has none
+    pub fn ssh_port_or(&self, default: u32) -> u32 {
+        self.ssh_port.unwrap_or(default)
     }
 
     /// Returns the username associated with the current server.
@@ -137,20 +309,95 @@ impl Server {
             whoami::username()
         }
     }
+
+    /// Private method for opening a TCP connection to the SSH port of the server.
+    /// Raises an error, if the address cannot be resolved, or the connection fails
+    /// within the timeout.
+    fn _open_tcp(&self, timeout_ms: u64) -> Result<TcpStream, Box<Error>> {
+        let addr = format!("{}:{}", self.ip, self.ssh_port());
+        let connection_error = |e: &dyn std::fmt::Display|
+            PafError::create_error(&format!("Could not connect to {}: {}", addr, e));
+
+        let sock_addr = addr.to_socket_addrs().map_err(|e| connection_error(&e))?
+            .next().ok_or_else(|| connection_error(&"address could not be resolved"))?;
+        let tcp = TcpStream::connect_timeout(&sock_addr, Duration::from_millis(timeout_ms))
+            .map_err(|e| connection_error(&e))?;
+        Ok(tcp)
+    }
+
+    /// Checks, if the SSH port of the server accepts TCP connections within a timeout.
+    /// It does not check, if the current machine can log in, see `check_ssh_auth` for that.
+    /// 
+    /// ## Arguments
+    /// * `timeout_ms` - Timeout for connecting in milliseconds
+    pub fn is_reachable(&self, timeout_ms: u64) -> bool {
+        self._open_tcp(timeout_ms).is_ok()
+    }
+
+    /// Checks, if the current machine can log in to the server via SSH. Opens a session,
+    /// and authenticates with the server's user (see `user`) using the identity file, if
+    /// configured, the password, if configured, or the SSH agent otherwise. Returns an error
+    /// stating, if the server could not be reached, or the authentication failed.
+    /// 
+    /// Only available with the `ssh` feature.
+    /// 
+    /// ## Arguments
+    /// * `timeout_ms` - Timeout for connecting and for each SSH operation in milliseconds
+    /// 
+    /// ## Examples
+    /// ```
+    /// let server = Server::new(None, "172.16.5.251".to_string(), None, Some("openpaf".to_string()));
+    /// if let Err(e) = server.check_ssh_auth(5000) {
+    ///     println!("{}", e);
+    /// }
+    /// ```
+    #[cfg(feature = "ssh")]
+    pub fn check_ssh_auth(&self, timeout_ms: u64) -> Result<(), Box<Error>> {
+        let addr = format!("{}:{}", self.ip, self.ssh_port());
+        let connection_error = |e: &dyn std::fmt::Display|
+            PafError::create_error(&format!("Could not connect to {} via SSH: {}", addr, e));
+        let tcp = self._open_tcp(timeout_ms)?;
+
+        let mut session = ssh2::Session::new().map_err(|e| connection_error(&e))?;
+        session.set_timeout(timeout_ms.min(u32::MAX as u64) as u32);
+        session.set_tcp_stream(tcp);
+        session.handshake().map_err(|e| connection_error(&e))?;
+
+        let user = self.user();
+        let auth = if let Some(identity) = &self.identity_file {
+            session.userauth_pubkey_file(&user, None, Path::new(identity), None)
+        } else if let Some(password) = &self.password {
+            session.userauth_password(&user, password)
+        } else {
+            session.userauth_agent(&user)
+        };
+
+        match auth {
+            Ok(_) if session.authenticated() => Ok(()),
+            Ok(_) => Err(PafError::create_error(&format!("Could not authenticate as {} on {}.", user, addr))),
+            Err(e) => Err(PafError::create_error(&format!("Could not authenticate as {} on {}: {}", user, addr, e)))
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::sync::Mutex;
+
+    // Tests reading or writing environment variables must hold this lock,
+    // as the test runner executes tests in parallel.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
     mod _sort {
         use super::super::*;
 
         #[test]
         fn sorts_servers() {
             let mut servers = vec![
-                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None}
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
             ];
             Server::_sort(&mut servers);
 
@@ -159,6 +406,42 @@ mod test {
             assert_eq!(servers[2].ip, "172.16.5.250");
             assert_eq!(servers[3].ip, "172.16.5.251");
         } 
+
+        #[test]
+        fn sorts_ips_numerically() {
+            // Servers without a port use the default port from the environment
+            let _lock = super::ENV_LOCK.lock().unwrap();
+            let mut servers = vec![
+                Server::new(None, "172.16.5.100".to_string(), None, None),
+                Server::new(None, "server.local".to_string(), None, None),
+                Server::new(None, "172.16.5.9".to_string(), None, None),
+                Server::new(None, "172.16.5.9".to_string(), Some(2222), None),
+                Server::new(None, "::1".to_string(), None, None)
+            ];
+            Server::_sort(&mut servers);
+
+            let ips: Vec<String> = servers.iter().map(|s| s.ip()).collect();
+            assert_eq!(ips, vec!["172.16.5.9", "172.16.5.9", "172.16.5.100", "::1", "server.local"]);
+            assert_eq!(servers[1].ssh_port, Some(2222));
+        }
+    }
+
+    mod sort_key {
+        use super::super::*;
+
+        #[test]
+        fn orders_numerically() {
+            let nine = Server::new(None, "10.0.0.9".to_string(), Some(22), None);
+            let hundred = Server::new(None, "10.0.0.100".to_string(), Some(22), None);
+            assert!(nine.sort_key() < hundred.sort_key());
+        }
+
+        #[test]
+        fn puts_unparsable_ips_last() {
+            let host = Server::new(None, "server.local".to_string(), Some(22), None);
+            let ipv6 = Server::new(None, "fe80::1".to_string(), Some(22), None);
+            assert!(ipv6.sort_key() < host.sort_key());
+        }
     }
 
     mod _get_ip {
@@ -171,9 +454,22 @@ mod test {
 
         #[test]
         fn returns_current_ip_if_no_arg() {
+            let _lock = super::ENV_LOCK.lock().unwrap();
             let curr_ip = machine_ip::get().unwrap().to_string();
             assert_eq!(Server::_get_ip(None).unwrap(), curr_ip);
         }
+
+        #[test]
+        fn prefers_env_var_over_autodetect() {
+            let _lock = super::ENV_LOCK.lock().unwrap();
+            env::set_var(MACHINE_IP_VAR, "10.11.12.13");
+            let res = Server::_get_ip(None);
+            let res_w_arg = Server::_get_ip(Some("172.16.1.1".to_string()));
+            env::remove_var(MACHINE_IP_VAR);
+
+            assert_eq!(res.unwrap(), "10.11.12.13");
+            assert_eq!(res_w_arg.unwrap(), "172.16.1.1");
+        }
     }
 
     mod new {
@@ -195,12 +491,13 @@ mod test {
 
         #[test]
         fn identifies_current_ip() {
+            let _lock = super::ENV_LOCK.lock().unwrap();
             let curr_ip = machine_ip::get().unwrap().to_string();
             let mut servers = vec![
-                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: curr_ip, ssh_port: None, user: None},
-                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None}
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: curr_ip, ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
             ];
 
             assert!(Server::next_server(&mut servers, None).is_ok())
@@ -209,10 +506,10 @@ mod test {
         #[test]
         fn errs_if_current_ip_not_in_list() {
             let mut servers = vec![
-                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None}
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
             ];
 
             assert!(Server::next_server(&mut servers, None).is_err())
@@ -221,10 +518,10 @@ mod test {
         #[test]
         fn accepts_optional_ip() {
             let mut servers = vec![
-                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None}
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
             ];
 
             assert!(Server::next_server(&mut servers, Some("172.16.5.250".to_string())).is_ok())
@@ -233,10 +530,10 @@ mod test {
         #[test]
         fn returns_correct_server() {
             let mut servers = vec![
-                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None}
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
             ];
 
             assert_eq!(Server::next_server(&mut servers, Some("172.16.5.250".to_string())).unwrap().ip, "172.16.5.251");
@@ -244,16 +541,78 @@ mod test {
         }
     }
 
+    mod next_server_from_head {
+        use super::super::*;
+
+        #[test]
+        fn starts_ring_from_head() {
+            let mut servers = vec![
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            Server::next_server_from_head(&mut servers, Some("172.16.5.250".to_string()), "172.13.1.121").unwrap();
+            let ips: Vec<&str> = servers.iter().map(|s| s.ip.as_str()).collect();
+            assert_eq!(ips, ["172.13.1.121", "172.16.5.250", "172.16.5.251", "172.11.3.110"]);
+        }
+
+        #[test]
+        fn returns_correct_server() {
+            let mut servers = vec![
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            assert_eq!(Server::next_server_from_head(&mut servers, Some("172.13.1.121".to_string()), "172.13.1.121").unwrap().ip, "172.16.5.250");
+            assert_eq!(Server::next_server_from_head(&mut servers, Some("172.16.5.251".to_string()), "172.13.1.121").unwrap().ip, "172.11.3.110");
+        }
+
+        #[test]
+        fn wraps_around_to_head() {
+            let mut servers = vec![
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            assert_eq!(Server::next_server_from_head(&mut servers, Some("172.11.3.110".to_string()), "172.13.1.121").unwrap().ip, "172.13.1.121");
+        }
+
+        #[test]
+        fn errs_if_head_not_in_list() {
+            let mut servers = vec![
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            assert!(Server::next_server_from_head(&mut servers, Some("172.16.5.250".to_string()), "10.0.0.1").is_err());
+        }
+
+        #[test]
+        fn errs_if_ip_not_in_list() {
+            let mut servers = vec![
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            assert!(Server::next_server_from_head(&mut servers, Some("10.0.0.1".to_string()), "172.13.1.121").is_err());
+        }
+    }
+
     mod remove_duplicates {
         use super::super::*;
 
         #[test]
         fn removes_duplicates() {
             let mut servers = vec![
-                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None},
-                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None}
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.13.1.121".to_string(), ssh_port: None, user: None, ..Default::default()}
             ];
             Server::remove_duplicates(&mut servers);
 
@@ -269,13 +628,110 @@ mod test {
         }
     }
 
+    mod render_chain {
+        use super::super::*;
+
+        #[test]
+        fn marks_current_server_in_order() {
+            let _lock = super::ENV_LOCK.lock().unwrap();
+            let mut servers = vec![
+                Server {name: Some("third".to_string()), ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: Some("second".to_string()), ip: "172.16.5.250".to_string(), ssh_port: Some(2222), user: None, ..Default::default()},
+                Server {name: None, ip: "172.11.3.110".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            let chain = Server::render_chain(&mut servers, Some("172.16.5.250".to_string()));
+
+            assert_eq!(chain, "-> (172.11.3.110:22)\n-> second (172.16.5.250:2222) *\n-> third (172.16.5.251:22)");
+        }
+
+        #[test]
+        fn does_not_mark_unknown_server() {
+            let mut servers = vec![
+                Server {name: Some("first".to_string()), ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            let chain = Server::render_chain(&mut servers, Some("10.0.0.1".to_string()));
+
+            assert!(!chain.contains("*"));
+        }
+    }
+
+    mod eq {
+        use super::super::*;
+
+        #[test]
+        fn compares_ip_and_port() {
+            let server = Server {name: Some("a".to_string()), ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()};
+            let renamed = Server {name: Some("b".to_string()), ip: "172.16.5.251".to_string(), ssh_port: Some(22), user: None, ..Default::default()};
+            let other_port = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: Some(2222), user: None, ..Default::default()};
+            let other_ip = Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()};
+
+            assert!(server == renamed);
+            assert!(server != other_port);
+            assert!(server != other_ip);
+        }
+    }
+
+    mod diff {
+        use super::super::*;
+
+        #[test]
+        fn detects_added_servers() {
+            let old = vec![
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            let new = vec![
+                Server {name: None, ip: "172.16.5.252".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            let (added, removed) = Server::diff(&old, &new);
+
+            assert_eq!(added.iter().map(|s| s.ip()).collect::<Vec<String>>(), vec!["172.16.5.251", "172.16.5.252"]);
+            assert!(removed.is_empty());
+        }
+
+        #[test]
+        fn detects_removed_servers() {
+            let old = vec![
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            let mut new = vec![
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            new.push(Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: Some(2222), user: None, ..Default::default()});
+            let (added, removed) = Server::diff(&old, &new);
+
+            assert_eq!(added.len(), 1);
+            assert_eq!(added[0].ssh_port_or(22), 2222);
+            assert_eq!(removed.len(), 1);
+            assert_eq!(removed[0].ip(), "172.16.5.251");
+        }
+
+        #[test]
+        fn returns_empty_vectors_if_unchanged() {
+            let old = vec![
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            let new = vec![
+                Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()},
+                Server {name: None, ip: "172.16.5.250".to_string(), ssh_port: None, user: None, ..Default::default()}
+            ];
+            let (added, removed) = Server::diff(&old, &new);
+
+            assert!(added.is_empty());
+            assert!(removed.is_empty());
+        }
+    }
+
     mod name {
         use super::super::*;
 
         #[test]
         fn returns_name_or_empty_string() {
-            let server = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None};
-            let named_server = Server {name: Some("me".to_string()), ip: "172.16.5.251".to_string(), ssh_port: None, user: None};
+            let server = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()};
+            let named_server = Server {name: Some("me".to_string()), ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()};
 
             assert_eq!(server.name(), "".to_string());
             assert_eq!(named_server.name(), "me".to_string());
@@ -287,7 +743,7 @@ mod test {
 
         #[test]
         fn returns_ip() {
-            let server = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None};
+            let server = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()};
 
             assert_eq!(server.ip(), "172.16.5.251".to_string());
         }
@@ -298,12 +754,44 @@ mod test {
 
         #[test]
         fn returns_port_or_default() {
-            let server = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None};
-            let server_w_port = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: Some(3000), user: None};
+            let _lock = super::ENV_LOCK.lock().unwrap();
+            let server = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()};
+            let server_w_port = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: Some(3000), user: None, ..Default::default()};
 
             assert_eq!(server.ssh_port(), 22);
             assert_eq!(server_w_port.ssh_port(), 3000);
         }
+
+        #[test]
+        fn prefers_env_default_over_22() {
+            let _lock = super::ENV_LOCK.lock().unwrap();
+            let server = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()};
+            let server_w_port = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: Some(3000), user: None, ..Default::default()};
+
+            env::set_var(DEFAULT_SSH_PORT_VAR, "2222");
+            let port = server.ssh_port();
+            let port_w_field = server_w_port.ssh_port();
+            env::set_var(DEFAULT_SSH_PORT_VAR, "not a port");
+            let port_w_invalid = server.ssh_port();
+            env::remove_var(DEFAULT_SSH_PORT_VAR);
+
+            assert_eq!(port, 2222);
+            assert_eq!(port_w_field, 3000);
+            assert_eq!(port_w_invalid, 22);
+        }
+    }
+
+    mod ssh_port_or {
+        use super::super::*;
+
+        #[test]
+        fn returns_port_or_provided_default() {
+            let server = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()};
+            let server_w_port = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: Some(3000), user: None, ..Default::default()};
+
+            assert_eq!(server.ssh_port_or(2222), 2222);
+            assert_eq!(server_w_port.ssh_port_or(2222), 3000);
+        }
     }
 
     mod user {
@@ -311,12 +799,72 @@ mod test {
 
         #[test]
         fn returns_user_or_default() {
-            let server = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None};
-            let server_w_user = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: Some("me".to_string())};
+            let server = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: None, ..Default::default()};
+            let server_w_user = Server {name: None, ip: "172.16.5.251".to_string(), ssh_port: None, user: Some("me".to_string()), ..Default::default()};
             let username = whoami::username();
 
             assert_eq!(server.user(), username);
             assert_eq!(server_w_user.user(), "me".to_string());
         }
     }
+
+    mod check_chain {
+        use super::super::*;
+        use std::net::TcpListener;
+
+        #[test]
+        fn probes_every_server() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let open_port = listener.local_addr().unwrap().port() as u32;
+            let closed_port = {
+                let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+                closed.local_addr().unwrap().port() as u32
+            };
+
+            let servers = vec![
+                Server::new(None, "127.0.0.1".to_string(), Some(closed_port), None),
+                Server::new(None, "127.0.0.1".to_string(), Some(open_port), None),
+                Server::new(None, "127.0.0.1".to_string(), Some(closed_port), None)
+            ];
+            let report = Server::check_chain(&servers, 1000);
+            assert_eq!(report, vec![
+                ("127.0.0.1".to_string(), false),
+                ("127.0.0.1".to_string(), true),
+                ("127.0.0.1".to_string(), false)
+            ]);
+        }
+
+        #[test]
+        fn reports_unresolvable_servers() {
+            let servers = vec![Server::new(None, "not a host".to_string(), Some(22), None)];
+            assert_eq!(Server::check_chain(&servers, 1000), vec![("not a host".to_string(), false)]);
+        }
+    }
+
+    // Requires a local SSH server with password authentication and a user
+    // `openpaf_user` with the password `openpaf123` (see the CI configuration).
+    #[cfg(feature = "ssh")]
+    mod check_ssh_auth {
+        use super::super::*;
+
+        #[test]
+        fn authenticates_with_password() {
+            let server = Server {name: None, ip: "127.0.0.1".to_string(), ssh_port: Some(22), user: Some("openpaf_user".to_string()), password: Some("openpaf123".to_string()), ..Default::default()};
+            assert!(server.check_ssh_auth(5000).is_ok());
+        }
+
+        #[test]
+        fn reports_auth_failure() {
+            let server = Server {name: None, ip: "127.0.0.1".to_string(), ssh_port: Some(22), user: Some("openpaf_user".to_string()), password: Some("wrong".to_string()), ..Default::default()};
+            let err = server.check_ssh_auth(5000).err().unwrap();
+            assert!(err.to_string().contains("Could not authenticate"));
+        }
+
+        #[test]
+        fn reports_connection_failure() {
+            let server = Server {name: None, ip: "127.0.0.1".to_string(), ssh_port: Some(1), user: Some("openpaf_user".to_string()), password: Some("openpaf123".to_string()), ..Default::default()};
+            let err = server.check_ssh_auth(5000).err().unwrap();
+            assert!(err.to_string().contains("Could not connect"));
+        }
+    }
 }
\ No newline at end of file
@@ -1,13 +1,23 @@
-use chrono::{TimeZone, Utc, Datelike, Timelike, Duration, NaiveDate};
+use chrono::{TimeZone, Utc, Local, FixedOffset, Datelike, Timelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
 use chrono::DateTime as ChronoDateTime;
-use chrono_tz::Tz;
+use chrono::format::{Item, StrftimeItems};
+use chrono_tz::{Tz, OffsetComponents, TZ_VARIANTS};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::error::Error;
+use std::iter;
 use super::timefreq::{TimeFreq, Resolution};
 use super::super::error::PafError;
 
 /// Constant for the application's accepted time format.
 const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
+thread_local! {
+    // The format used by methods without an explicit format. See `DateTime::set_default_format`.
+    static DEFAULT_FORMAT: RefCell<String> = RefCell::new(TIMESTAMP_FORMAT.to_string());
+}
+
 /// A simple wrapper around `chrono::DateTime`, allowing for
 /// creating methods without overriding anything by accident
 pub struct DateTime {
@@ -33,7 +43,7 @@ impl DateTime {
 
         // For GMT+X and GMT-X timezones, preprend it with Etc/, like in the IANA DB
         // TODO: Consider inverting the sign
-        let tz_str = if (raw_str.contains("+") || raw_str.contains("-")) && !raw_str.starts_with("Etc/")
+        let tz_str = if raw_str.starts_with("GMT+") || raw_str.starts_with("GMT-")
             {String::from("Etc/") + raw_str} else {raw_str.to_string()};
 
         let tz: Tz = tz_str.parse()?;
@@ -191,9 +201,30 @@ impl DateTime {
         }
     }
 
+    /// Utility function for truncating a naive datetime to the start of the unit
+    /// represented by a resolution (e.g. `Resolution::Day` truncates to midnight).
+    /// 
+    /// ## Arguments
+    /// * `naive` - A naive datetime
+    /// * `res` - The resolution to truncate to
+    fn _truncate_naive(naive: &NaiveDateTime, res: &Resolution) -> NaiveDateTime {
+        let date = naive.date();
+        match res {
+            Resolution::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+            Resolution::Month => date.with_day(1).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+            Resolution::Day => date.and_hms_opt(0, 0, 0).unwrap(),
+            Resolution::Hour => date.and_hms_opt(naive.hour(), 0, 0).unwrap(),
+            Resolution::Minute => date.and_hms_opt(naive.hour(), naive.minute(), 0).unwrap(),
+            Resolution::Second | Resolution::None => date.and_hms_opt(naive.hour(), naive.minute(), naive.second()).unwrap()
+        }
+    }
+
     /// Utility method for calculating the next occurrence of a time pattern relative to
     /// a `DateTime` object. For more information, see `DateTime::next_occurrence`.
     fn _next_occurrence(timestamp: &str, ref_date: &DateTime) -> Result<DateTime, Box<Error>> {
+        if let Some((week, time)) = TimeFreq::parse_iso_week_pattern(timestamp)? {
+            return DateTime::_next_iso_week_occurrence(week, &time, ref_date);
+        }
         let parsed: TimeFreq = TimeFreq::from_timestamp(timestamp, false)?;
 
         // Merge current time with available relative time components
@@ -206,28 +237,74 @@ impl DateTime {
         // one cycle according to its resolution
         // e.g. if the relative time is 23:59:04, add a day
         if merged.is_passed(Some(&ref_date)) {
-            match parsed.resolution {
-                Resolution::Year => return Err(PafError::create_error("Too specific timestamp, there is no next occurrence.")),
-                Resolution::Month => merged.add("1-0-0 0:0:0").unwrap(),
-                Resolution::Day => {
-                    // We handle month additions differently, as days after 28 are not consistent
-                    // in every month
-                    let mut num_months = 1;
-                    while merged.add(&format!("0-{}-0 0:0:0", num_months)).is_err() {
-                        num_months += 1;
-                    }
-
-                },
-                Resolution::Hour => merged.add("0-0-1 0:0:0").unwrap(),
-                Resolution::Minute => merged.add("1:0:0").unwrap(),
-                Resolution::Second => merged.add("0:1:0").unwrap(),
-                Resolution::None => {}
+            match parsed.cycle_length() {
+                Some(cycle) => merged._add_timefreq(&cycle),
+                None => return Err(PafError::create_error("Too specific timestamp, there is no next occurrence."))
             }
         }
 
         Ok(merged)
     }
 
+    /// Utility method for calculating the next occurrence of a week-of-year pattern (i.e. the
+    /// time of day on Monday of an ISO week) relative to a `DateTime` object. Years without
+    /// the week are skipped.
+    fn _next_iso_week_occurrence(week: u32, time: &TimeFreq, ref_date: &DateTime) -> Result<DateTime, Box<Error>> {
+        let (year, _, _) = ref_date.iso_week();
+        // Years with 53 ISO weeks are at most 6 years apart
+        for candidate_year in year..=year + 7 {
+            if let Ok(monday) = DateTime::from_iso_week(candidate_year, week, Weekday::Mon) {
+                let occurrence = monday.dt + Duration::seconds(time.calc_duration());
+                if occurrence >= ref_date.dt {
+                    return Ok(DateTime {dt: occurrence});
+                }
+            }
+        }
+        Err(PafError::create_error(&format!("There is no next occurrence of ISO week {}.", week)))
+    }
+
+    /// Sets the default format used by methods without an explicit format (`from_timestamp`,
+    /// `to_timestamp`, `to_json_value`, `from_json_value`, and `format_epochs`), which is
+    /// %Y-%m-%d %H:%M:%S initially. Raises an error, if the format string is not valid.
+    /// 
+    /// The default is global state, but it is stored per thread, therefore, it does not leak
+    /// into other threads (e.g. parallel tests), but it has to be set on every thread, which
+    /// handles timestamps. Prefer passing a format explicitly to `DateTime::format` in library code.
+    /// 
+    /// ## Arguments
+    /// * `fmt` A format string
+    /// 
+    /// ## Examples
+    /// ```
+    /// DateTime::set_default_format("%Y-%m-%dT%H:%M:%S").unwrap();
+    /// let dt: DateTime = DateTime::from_epoch(0);
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "1970-01-01T00:00:00");
+    /// ```
+    pub fn set_default_format(fmt: &str) -> Result<(), Box<Error>> {
+        DateTime::_validate_format(fmt)?;
+        DEFAULT_FORMAT.with(|f| *f.borrow_mut() = fmt.to_string());
+        Ok(())
+    }
+
+    /// Restores the default format to %Y-%m-%d %H:%M:%S on the current thread.
+    /// For more information, see `DateTime::set_default_format`.
+    pub fn reset_default_format() {
+        DEFAULT_FORMAT.with(|f| *f.borrow_mut() = TIMESTAMP_FORMAT.to_string());
+    }
+
+    /// Utility function for checking a format string, as formatting with an invalid one panics.
+    fn _validate_format(fmt: &str) -> Result<(), Box<Error>> {
+        if StrftimeItems::new(fmt).any(|item| item == Item::Error) {
+            return Err(PafError::create_error(&format!("Invalid format string {}.", fmt)));
+        }
+        Ok(())
+    }
+
+    /// Utility function for reading the default format of the current thread.
+    fn _default_format() -> String {
+        DEFAULT_FORMAT.with(|f| f.borrow().clone())
+    }
+
     /// Clones the `DateTime` object.
     /// 
     /// ## Examples
@@ -243,7 +320,8 @@ impl DateTime {
     /// it raises an error. If a timezone is provided, the string is
     /// treated as local, and converted to UTC.
     /// 
-    /// Time string must be formatted the following way: %Y-%m-%d %H:%M:%S
+    /// Time string must be formatted the following way: %Y-%m-%d %H:%M:%S, unless a different
+    /// default is set with `DateTime::set_default_format`.
     /// 
     /// For valid timezone strings, see the [IANA database](https://www.iana.org/time-zones)
     /// or a [browsable extract](https://en.wikipedia.org/wiki/List_of_tz_database_time_zones).
@@ -261,27 +339,310 @@ impl DateTime {
     /// assert_eq!(dt.to_timestamp(None), "2019-01-01 11:00:00");
     /// ```
     pub fn from_timestamp(ts: &str, timezone: Option<&str>) -> Result<DateTime, Box<Error>> {
+        DateTime::_from_timestamp_fmt(ts, &DateTime::_default_format(), timezone)
+    }
+
+    /// Private method for parsing a datetime string in an explicit format. Strings built or
+    /// stored by the application are parsed with `TIMESTAMP_FORMAT`, independently of the
+    /// default format.
+    fn _from_timestamp_fmt(ts: &str, fmt: &str, timezone: Option<&str>) -> Result<DateTime, Box<Error>> {
         let tz: Tz = DateTime::_read_timezone(timezone)?;
-        let dt = tz.datetime_from_str(ts, TIMESTAMP_FORMAT)?.with_timezone(&Utc);
+        let dt = tz.datetime_from_str(ts, fmt)?.with_timezone(&Utc);
         Ok(DateTime {dt: dt})
     }
 
+    /// Tries to create a new `DateTime` object from a string representing local time in the
+    /// system's timezone, and converts it to UTC. The result depends on the timezone of the
+    /// host, therefore, `from_timestamp` with an explicit timezone should be preferred, when
+    /// the timezone is known. On failure, or if the local time is ambiguous or does not exist
+    /// due to a DST change, it raises an error.
+    /// 
+    /// ## Arguments
+    /// * `ts` - A datetime string in the default format
+    /// 
+    /// ## Examples
+    /// ```
+    /// // The host is in CET
+    /// let dt: DateTime = DateTime::from_timestamp_local("2019-01-01 12:00:00").unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-01-01 11:00:00");
+    /// ```
+    pub fn from_timestamp_local(ts: &str) -> Result<DateTime, Box<Error>> {
+        let naive = NaiveDateTime::parse_from_str(ts, &DateTime::_default_format())?;
+        let local = Local.from_local_datetime(&naive).single()
+            .ok_or_else(|| PafError::create_error(&format!("Local time {} is ambiguous or does not exist.", ts)))?;
+        Ok(DateTime {dt: local.with_timezone(&Utc)})
+    }
+
+    /// Tries to create a new `DateTime` object from a string, and reports how precisely it was
+    /// specified. Trailing components of the %Y-%m-%d %H:%M:%S format can be omitted (e.g.
+    /// `"2019-01-01"` or `"2019-01-01 12:30"`), in which case they are filled with their smallest
+    /// values, and a coarser resolution is returned. On failure, it raises an error. If a timezone
+    /// is provided, the string is treated as local, and converted to UTC.
+    /// 
+    /// ## Arguments
+    /// * `ts` - A full or truncated datetime string
+    /// * `timezone` An optional timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let (dt, res) = DateTime::from_timestamp_with_resolution("2019-01-01", None).unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-01-01 00:00:00");
+    /// assert!(res == Resolution::Day);
+    /// ```
+    pub fn from_timestamp_with_resolution(ts: &str, timezone: Option<&str>) -> Result<(DateTime, Resolution), Box<Error>> {
+        let parts: Vec<&str> = ts.split_whitespace().collect();
+        if parts.len() > 2 {
+            return Err(PafError::create_error(&format!("Invalid timestamp {}.", ts)));
+        }
+        let mut date: Vec<&str> = parts.first().map(|d| d.split('-').collect()).unwrap_or_default();
+        let mut time: Vec<&str> = parts.get(1).map(|t| t.split(':').collect()).unwrap_or_default();
+
+        let res = match (date.len(), time.len()) {
+            (1, 0) => Resolution::Year,
+            (2, 0) => Resolution::Month,
+            (3, 0) => Resolution::Day,
+            (3, 1) => Resolution::Hour,
+            (3, 2) => Resolution::Minute,
+            (3, 3) => Resolution::Second,
+            _ => return Err(PafError::create_error(&format!("Invalid timestamp {}.", ts)))
+        };
+
+        // Fill omitted components with their smallest values
+        date.resize(3, "01");
+        time.resize(3, "00");
+        let full = format!("{} {}", date.join("-"), time.join(":"));
+        Ok((DateTime::_from_timestamp_fmt(&full, TIMESTAMP_FORMAT, timezone)?, res))
+    }
+
+    /// Tries to create a new `DateTime` object at midnight of a calendar date. On failure
+    /// (e.g. February 30), it raises an error. If a timezone is provided, midnight is
+    /// treated as local, and converted to UTC.
+    /// 
+    /// ## Arguments
+    /// * `year` - The year
+    /// * `month` - The month (1-12)
+    /// * `day` - The day of the month (1-31)
+    /// * `timezone` An optional timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_date(2019, 1, 1, Some("CET")).unwrap();
+    /// assert_eq!(dt.to_timestamp(None), "2018-12-31 23:00:00");
+    /// ```
+    pub fn from_date(year: i32, month: u32, day: u32, timezone: Option<&str>) -> Result<DateTime, Box<Error>> {
+        let tz: Tz = DateTime::_read_timezone(timezone)?;
+        let date = NaiveDate::from_ymd_opt(year, month, day)
+            .ok_or_else(|| PafError::create_error(&format!("Invalid date {}-{:0w$}-{:0w$}.", year, month, day, w = 2)))?;
+        let dt = tz.from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap()).earliest()
+            .ok_or_else(|| PafError::create_error(&format!("There is no midnight on {} in the provided timezone.", date)))?;
+        Ok(DateTime {dt: dt.with_timezone(&Utc)})
+    }
+
+    /// Tries to create a new `DateTime` object at midnight of the nth occurrence of a weekday
+    /// in a month (e.g. the 3rd Tuesday). On failure (e.g. there is no 5th Friday in the month),
+    /// it raises an error. If a timezone is provided, midnight is treated as local, and converted to UTC.
+    /// 
+    /// ## Arguments
+    /// * `year` - The year
+    /// * `month` - The month (1-12)
+    /// * `weekday` - The weekday
+    /// * `n` - The occurrence of the weekday in the month (1-5)
+    /// * `timezone` An optional timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::nth_weekday(2019, 7, Weekday::Tue, 3, None).unwrap();
+    /// assert_eq!(dt.to_timestamp(None), "2019-07-16 00:00:00");
+    /// ```
+    pub fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8, timezone: Option<&str>) -> Result<DateTime, Box<Error>> {
+        if !(1..=5).contains(&n) {
+            return Err(PafError::create_error(&format!("Invalid weekday occurrence {}. It must be between 1 and 5.", n)));
+        }
+
+        let first = NaiveDate::from_ymd_opt(year, month, 1)
+            .ok_or_else(|| PafError::create_error(&format!("Invalid month {}-{:0w$}.", year, month, w = 2)))?;
+        let offset = (7 + weekday.num_days_from_monday() - first.weekday().num_days_from_monday()) % 7;
+        let day = 1 + offset + 7 * (n as u32 - 1);
+
+        if NaiveDate::from_ymd_opt(year, month, day).is_none() {
+            return Err(PafError::create_error(&format!("There is no occurrence {} of {:?} in {}-{:0w$}.",
+                n, weekday, year, month, w = 2)));
+        }
+        DateTime::from_date(year, month, day, timezone)
+    }
+
+    /// Tries to create a new `DateTime` object at midnight UTC of a weekday in an ISO week
+    /// (e.g. Monday of week 14). On failure (e.g. the year has only 52 ISO weeks), it raises
+    /// an error. Note that the ISO year can differ from the calendar year around new year.
+    /// 
+    /// ## Arguments
+    /// * `year` - The ISO year
+    /// * `week` - The ISO week (1-53)
+    /// * `weekday` - The weekday
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_iso_week(2019, 14, Weekday::Mon).unwrap();
+    /// assert_eq!(dt.to_timestamp(None), "2019-04-01 00:00:00");
+    /// ```
+    pub fn from_iso_week(year: i32, week: u32, weekday: Weekday) -> Result<DateTime, Box<Error>> {
+        if !(1..=53).contains(&week) {
+            return Err(PafError::create_error(&format!("Invalid ISO week {}. It must be between 1 and 53.", week)));
+        }
+
+        let date = NaiveDate::from_isoywd_opt(year, week, weekday)
+            .ok_or_else(|| PafError::create_error(&format!("There is no ISO week {} in {}.", week, year)))?;
+        Ok(DateTime {dt: Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap())})
+    }
+
     /// Creates a new `DateTime` object from an integer. The integer is
     /// an epoch time, which is the number of seconds since January 1, 1970 UTC.
     pub fn from_epoch(epoch: i64) -> DateTime {
         DateTime {dt: Utc.timestamp(epoch, 0)}
     }
 
+    /// Creates a new `DateTime` object at the local midnight of the day containing an epoch
+    /// time in a timezone. If local midnight does not exist on that day due to a DST change
+    /// (e.g. clocks jump from 00:00 to 01:00), the first existing local minute of the day is
+    /// used instead. Raises an error, if the timezone is not valid, or the epoch is out of range.
+    /// 
+    /// ## Arguments
+    /// * `epoch` - An epoch time
+    /// * `timezone` - A timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// // 2019-07-15 12:00:00 UTC
+    /// let dt: DateTime = DateTime::local_midnight(1_563_192_000, "CET").unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-07-14 22:00:00");
+    /// ```
+    pub fn local_midnight(epoch: i64, timezone: &str) -> Result<DateTime, Box<Error>> {
+        let tz: Tz = DateTime::_read_timezone(Some(timezone))?;
+        let utc = Utc.timestamp_opt(epoch, 0).single()
+            .ok_or_else(|| PafError::create_error(&format!("Epoch time {} is out of range.", epoch)))?;
+        let midnight = utc.with_timezone(&tz).date_naive().and_hms_opt(0, 0, 0).unwrap();
+
+        // Local midnight can fall in a DST gap, which lasts at most a few hours
+        let dt = (0..24 * 60)
+            .map(|m| midnight + Duration::minutes(m))
+            .find_map(|local| tz.from_local_datetime(&local).earliest())
+            .ok_or_else(|| PafError::create_error(&format!("There is no local time on {} in the provided timezone.", midnight.date())))?;
+        Ok(DateTime {dt: dt.with_timezone(&Utc)})
+    }
+
     /// Creates a `DateTime` object from the current time in UTC.
     pub fn now() -> DateTime {
         DateTime {dt: Utc::now()}
     }
 
+    /// Utility method for parsing a relative time expression relative to a `DateTime` object
+    /// standing for `now`. For more information, see `DateTime::parse_relative`.
+    fn _parse_relative(expr: &str, ref_date: &DateTime) -> Result<DateTime, Box<Error>> {
+        let offset = match expr.trim().strip_prefix("now") {
+            Some(offset) => offset.trim(),
+            None => return Err(PafError::create_error(&format!("Invalid relative time expression {}.", expr)))
+        };
+
+        let mut dt = ref_date.clone();
+        if let Some(ts) = offset.strip_prefix('+') {
+            dt.add(ts)?;
+        } else if let Some(ts) = offset.strip_prefix('-') {
+            dt.subtract(ts)?;
+        } else if !offset.is_empty() {
+            return Err(PafError::create_error(&format!("Invalid relative time expression {}.", expr)));
+        }
+        Ok(dt)
+    }
+
+    /// Tries to create a new `DateTime` object from a relative time expression. Expressions
+    /// consist of `now`, and an optional offset, which is a partial time string prefixed with
+    /// `+` or `-`. On failure, it raises an error.
+    /// 
+    /// For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// 
+    /// ## Arguments
+    /// * `expr` - A relative time expression
+    /// 
+    /// ## Examples
+    /// ```
+    /// // It is 2019-07-26 12:00:00
+    /// let dt: DateTime = DateTime::parse_relative("now+1:30:0").unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-07-26 13:30:00");
+    /// 
+    /// let dt: DateTime = DateTime::parse_relative("now-1 0:0:0").unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-07-25 12:00:00");
+    /// ```
+    pub fn parse_relative(expr: &str) -> Result<DateTime, Box<Error>> {
+        let dt = DateTime::now();
+
+        DateTime::_parse_relative(expr, &dt)
+    }
+
+    /// Utility method for parsing a natural language time expression relative to a `DateTime`
+    /// object standing for `now`. For more information, see `DateTime::parse_natural`.
+    fn _parse_natural(expr: &str, timezone: Option<&str>, ref_date: &DateTime) -> Result<DateTime, Box<Error>> {
+        let tz: Tz = DateTime::_read_timezone(timezone)?;
+        let invalid = || PafError::create_error(&format!("Unrecognized time expression {}.", expr));
+        let lower = expr.to_lowercase();
+        let tokens: Vec<&str> = lower.split_whitespace().collect();
+        let today = ref_date.dt.with_timezone(&tz).date_naive();
+
+        let (date, rest) = match tokens.as_slice() {
+            ["today", rest @ ..] => (today, rest),
+            ["tomorrow", rest @ ..] => (today + Duration::days(1), rest),
+            ["yesterday", rest @ ..] => (today - Duration::days(1), rest),
+            ["next", weekday, rest @ ..] => {
+                let weekday: Weekday = weekday.parse().map_err(|_| invalid())?;
+                let ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+                (today + Duration::days(if ahead == 0 {7} else {ahead as i64}), rest)
+            },
+            _ => return Err(invalid())
+        };
+
+        let time = match rest {
+            [] => NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+            [time] => NaiveTime::parse_from_str(time, "%H:%M:%S").map_err(|_| invalid())?,
+            _ => return Err(invalid())
+        };
+
+        let local = date.and_time(time);
+        let dt = tz.from_local_datetime(&local).earliest()
+            .ok_or_else(|| PafError::create_error(&format!("Local time {} does not exist.", local)))?;
+        Ok(DateTime {dt: dt.with_timezone(&Utc)})
+    }
+
+    /// Tries to create a new `DateTime` object from a natural language expression relative to
+    /// the current day in a timezone. On failure (e.g. the expression is not recognized), it
+    /// raises an error. Expressions are case insensitive, and consist of a day, and an optional
+    /// time in the %H:%M:%S format, which defaults to midnight. Recognized days are:
+    /// * `today`, `tomorrow`, `yesterday`
+    /// * `next <weekday>` - the first such weekday after today, i.e. a week later, if it is today
+    /// 
+    /// ## Arguments
+    /// * `expr` - A natural language time expression
+    /// * `timezone` - An optional timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// // It is 2019-07-26 12:00:00, Friday
+    /// let dt: DateTime = DateTime::parse_natural("tomorrow 09:00:00", None).unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-07-27 09:00:00");
+    /// 
+    /// let dt: DateTime = DateTime::parse_natural("next monday", None).unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-07-29 00:00:00");
+    /// ```
+    pub fn parse_natural(expr: &str, timezone: Option<&str>) -> Result<DateTime, Box<Error>> {
+        let dt = DateTime::now();
+
+        DateTime::_parse_natural(expr, timezone, &dt)
+    }
+
     /// Serializes the `DateTime` object to a string. On failure,
     /// it raises an error. If a timezone is provided, the string represents
     /// time in the provided timezone.
     /// 
-    /// Time string is formatted the following way: %Y-%m-%d %H:%M:%S
+    /// Time string is formatted the following way: %Y-%m-%d %H:%M:%S, unless a different
+    /// default is set with `DateTime::set_default_format`.
     /// 
     /// For valid timezone strings, see the [IANA database](https://www.iana.org/time-zones)
     /// or a [browsable extract](https://en.wikipedia.org/wiki/List_of_tz_database_time_zones).
@@ -298,17 +659,235 @@ impl DateTime {
     /// assert_eq!(dt.to_timestamp(Some("CET")), "2019-01-01 12:00:00");
     /// ```
     pub fn to_timestamp(&self, timezone: Option<&str>) -> Result<String, Box<Error>> {
+        self.format(&DateTime::_default_format(), timezone)
+    }
+
+    /// Serializes the `DateTime` object to a string with a custom format. On failure (e.g. an
+    /// invalid format or timezone), it raises an error. If a timezone is provided, the string
+    /// represents time in the provided timezone.
+    /// 
+    /// For valid format specifiers, see the
+    /// [chrono documentation](https://docs.rs/chrono/0.4/chrono/format/strftime/index.html).
+    /// 
+    /// ## Arguments
+    /// * `fmt` A format string
+    /// * `timezone` An optional timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+    /// assert_eq!(dt.format("%Y-%m-%d", None), "2019-01-01");
+    /// ```
+    pub fn format(&self, fmt: &str, timezone: Option<&str>) -> Result<String, Box<Error>> {
         let tz: Tz = DateTime::_read_timezone(timezone)?;
-        let stamp = self.dt.with_timezone(&tz).format(TIMESTAMP_FORMAT).to_string();
+        DateTime::_validate_format(fmt)?;
+        let stamp = self.dt.with_timezone(&tz).format(fmt).to_string();
+        Ok(stamp)
+    }
+
+    /// Serializes the `DateTime` object to a string in the default format (see
+    /// `DateTime::set_default_format`), representing the local time at a fixed UTC
+    /// offset. Offsets do not have to be whole hours. Raises an error, if the offset is not
+    /// strictly between -24 and +24 hours.
+    /// 
+    /// ## Arguments
+    /// * `offset_secs` - The offset east of UTC in seconds
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+    /// assert_eq!(dt.shift_to_offset(5 * 3600 + 30 * 60).unwrap(), "2019-01-01 17:30:00");
+    /// ```
+    pub fn shift_to_offset(&self, offset_secs: i32) -> Result<String, Box<Error>> {
+        let offset = FixedOffset::east_opt(offset_secs)
+            .ok_or_else(|| PafError::create_error(&format!("UTC offset {} is out of range.", offset_secs)))?;
+        let stamp = self.dt.with_timezone(&offset).format(&DateTime::_default_format()).to_string();
         Ok(stamp)
     }
 
+    /// Formats a batch of epoch times (UNIX timestamps) in a single timezone. The timezone
+    /// and the format string are parsed only once, therefore, it is faster than formatting
+    /// `DateTime` objects one by one. If there is no format provided, the default format
+    /// is used (see `DateTime::set_default_format`). Raises an error on an invalid timezone,
+    /// format string, or an out of range epoch time.
+    /// 
+    /// ## Arguments
+    /// * `epochs` - The epoch times
+    /// * `timezone` - An optional timezone string
+    /// * `fmt` - An optional format string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let stamps = DateTime::format_epochs(&[0, 86_400], Some("CET"), None).unwrap();
+    /// assert_eq!(stamps, vec!["1970-01-01 01:00:00", "1970-01-02 01:00:00"]);
+    /// ```
+    pub fn format_epochs(epochs: &[i64], timezone: Option<&str>, fmt: Option<&str>) -> Result<Vec<String>, Box<Error>> {
+        let tz: Tz = DateTime::_read_timezone(timezone)?;
+        let default = DateTime::_default_format();
+        let items: Vec<Item> = StrftimeItems::new(fmt.unwrap_or(&default)).collect();
+        if items.contains(&Item::Error) {
+            return Err(PafError::create_error(&format!("Invalid format string {}.", fmt.unwrap_or(&default))));
+        }
+
+        epochs.iter().map(|&epoch| {
+            let dt = Utc.timestamp_opt(epoch, 0).single()
+                .ok_or_else(|| PafError::create_error(&format!("Epoch time {} is out of range.", epoch)))?;
+            Ok(dt.with_timezone(&tz).format_with_items(items.iter()).to_string())
+        }).collect()
+    }
+
     /// Calculates and returns the epoch time (UNIX timestamp) from the current
     /// `DateTime` object.
     pub fn to_epoch(&self) -> i64 {
         self.dt.timestamp()
     }
 
+    /// Returns the ISO 8601 year, week number, and weekday (1 for Monday to 7 for Sunday)
+    /// of the `DateTime` object in UTC. The ISO year can differ from the calendar year
+    /// around January 1, as the first ISO week is the one containing the first Thursday.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2021-01-01 12:00:00", None).unwrap();
+    /// assert_eq!(dt.iso_week(), (2020, 53, 5));
+    /// ```
+    pub fn iso_week(&self) -> (i32, u32, u32) {
+        let week = self.dt.iso_week();
+        (week.year(), week.week(), self.dt.weekday().number_from_monday())
+    }
+
+    /// Serializes the `DateTime` object to 8 bytes containing its epoch time as a big-endian
+    /// signed integer, which is suitable for fixed-width records. Sub-second precision is lost.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_epoch(1);
+    /// assert_eq!(dt.to_bytes(), [0, 0, 0, 0, 0, 0, 0, 1]);
+    /// ```
+    pub fn to_bytes(&self) -> [u8; 8] {
+        self.to_epoch().to_be_bytes()
+    }
+
+    /// Creates a new `DateTime` object from 8 bytes containing an epoch time as a big-endian
+    /// signed integer. For the format, see `DateTime::to_bytes`. Like `DateTime::from_epoch`,
+    /// it panics, if the epoch time is out of range.
+    /// 
+    /// ## Arguments
+    /// * `b` - The serialized epoch time
+    pub fn from_bytes(b: [u8; 8]) -> DateTime {
+        DateTime::from_epoch(i64::from_be_bytes(b))
+    }
+
+    /// Converts the `DateTime` object to a JSON string containing its UTC timestamp. The
+    /// timestamp is always in the %Y-%m-%d %H:%M:%S format, regardless of the default format.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+    /// assert_eq!(dt.to_json_value(), Value::String(String::from("2019-01-01 12:00:00")));
+    /// ```
+    pub fn to_json_value(&self) -> Value {
+        Value::String(self.dt.format(TIMESTAMP_FORMAT).to_string())
+    }
+
+    /// Tries to create a new `DateTime` object from a JSON string containing a UTC timestamp
+    /// in the %Y-%m-%d %H:%M:%S format, regardless of the default format. On failure (e.g. the value is not a string, or not a valid timestamp), it raises an error.
+    /// 
+    /// ## Arguments
+    /// * `v` - A JSON value
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_json_value(&json!("2019-01-01 12:00:00")).unwrap();
+    /// ```
+    pub fn from_json_value(v: &Value) -> Result<DateTime, Box<Error>> {
+        match v.as_str() {
+            Some(ts) => DateTime::_from_timestamp_fmt(ts, TIMESTAMP_FORMAT, None),
+            None => Err(PafError::create_error(&format!("Invalid JSON timestamp {}.", v)))
+        }
+    }
+
+    /// Rounds the `DateTime` object down to the previous multiple of an interval in seconds,
+    /// counted from the epoch (e.g. `300` floors to 5 minute buckets). If the interval is not
+    /// positive, returns an unchanged copy. Results out of range saturate at the earliest
+    /// representable time.
+    /// 
+    /// ## Arguments
+    /// * `interval_secs` - The interval in seconds
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:04:59", None).unwrap();
+    /// assert_eq!(dt.floor_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:00:00");
+    /// ```
+    pub fn floor_to(&self, interval_secs: i64) -> DateTime {
+        if interval_secs <= 0 {
+            return self.clone();
+        }
+        let epoch = self.to_epoch();
+        DateTime::_from_epoch_saturating(epoch.saturating_sub(epoch.rem_euclid(interval_secs)))
+    }
+
+    /// Rounds the `DateTime` object up to the next multiple of an interval in seconds,
+    /// counted from the epoch (e.g. `300` ceils to 5 minute buckets). If the interval is not
+    /// positive, returns an unchanged copy. Results out of range saturate at the latest
+    /// representable time.
+    /// 
+    /// ## Arguments
+    /// * `interval_secs` - The interval in seconds
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:00:01", None).unwrap();
+    /// assert_eq!(dt.ceil_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:05:00");
+    /// ```
+    pub fn ceil_to(&self, interval_secs: i64) -> DateTime {
+        if interval_secs <= 0 {
+            return self.clone();
+        }
+        let epoch = self.to_epoch();
+        let remainder = epoch.rem_euclid(interval_secs);
+        if remainder == 0 {
+            self.clone()
+        } else {
+            DateTime::_from_epoch_saturating(epoch.saturating_add(interval_secs - remainder))
+        }
+    }
+
+    /// Rounds the `DateTime` object to the nearest multiple of an interval in seconds,
+    /// counted from the epoch (e.g. `300` rounds to 5 minute buckets). Ties are rounded up.
+    /// If the interval is not positive, returns an unchanged copy. Results out of range
+    /// saturate at the earliest or latest representable time.
+    /// 
+    /// ## Arguments
+    /// * `interval_secs` - The interval in seconds
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:02:30", None).unwrap();
+    /// assert_eq!(dt.round_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:05:00");
+    /// ```
+    pub fn round_to(&self, interval_secs: i64) -> DateTime {
+        if interval_secs <= 0 {
+            return self.clone();
+        }
+        let epoch = self.to_epoch();
+        let remainder = epoch.rem_euclid(interval_secs);
+        if remainder >= interval_secs - remainder {
+            DateTime::_from_epoch_saturating(epoch.saturating_add(interval_secs - remainder))
+        } else {
+            DateTime::_from_epoch_saturating(epoch.saturating_sub(remainder))
+        }
+    }
+
+    /// Private method for creating a `DateTime` object from an epoch time, clamped to the
+    /// representable range.
+    fn _from_epoch_saturating(epoch: i64) -> DateTime {
+        let min = ChronoDateTime::<Utc>::MIN_UTC.timestamp();
+        let max = ChronoDateTime::<Utc>::MAX_UTC.timestamp();
+        DateTime::from_epoch(epoch.clamp(min, max))
+    }
+
     /// Adds a partial time to the `DateTime` object. Partial times must be
     /// provided as strings in the general format %Y-%m-%d %H:%M:%S. The method
     /// is void, but on failure, it raises an error.
@@ -333,7 +912,13 @@ impl DateTime {
     /// ```
     pub fn add(&mut self, timestamp: &str) -> Result<(), Box<Error>> {
         let parsed: TimeFreq = TimeFreq::from_timestamp(timestamp, true)?;
+        self._add_timefreq(&parsed);
+        Ok(())
+    }
 
+    /// Utility method for adding a parsed partial time to the `DateTime` object.
+    /// For more information, see `DateTime::add`.
+    fn _add_timefreq(&mut self, parsed: &TimeFreq) {
         // Handle years
         if parsed.years > 0 {
             self.dt = self.dt.with_year(self.dt.year() + parsed.years as i32).unwrap();
@@ -341,7 +926,7 @@ impl DateTime {
 
         // Handle months
         if parsed.months > 0 {
-            if parsed.months + self.dt.month() >= 12 {
+            if parsed.months + self.dt.month() > 12 {
                 // We cannot have more than 12 months at this point
                 self.dt = self.dt.with_year(self.dt.year() + 1).unwrap();
 
@@ -355,7 +940,6 @@ impl DateTime {
         // Add the rest of it as a single duration
         let dur = Duration::seconds(parsed.calc_duration());
         self.dt = self.dt + dur;
-        Ok(())
     }
 
     /// Subtracts a partial time from the `DateTime` object. Partial times must be
@@ -430,535 +1014,2914 @@ impl DateTime {
         }
     }
 
-    /// Calculates the next occurrence of a partial time string, and creates a
-    /// `DateTime` object as a result. If it fails, it raises an error. Partial
-    /// times must be provided as strings in the general format %Y-%m-%d %H:%M:%S.
+    /// Checks if two `DateTime` objects are equal after truncating both to a resolution in UTC
+    /// (e.g. `Resolution::Minute` ignores the seconds).
     /// 
-    /// For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// ## Arguments
+    /// * `other` - The `DateTime` object to compare to
+    /// * `res` - The resolution of the comparison
     /// 
-    /// Using it with days beyond 28, and with February 29 may cause unexpected results.
-    /// For more information, see the documentation of `DateTime::_add_months`.
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:30:05", None).unwrap();
+    /// let other: DateTime = DateTime::from_timestamp("2019-01-01 12:30:55", None).unwrap();
+    /// assert!(dt.eq_to_resolution(&other, Resolution::Minute));
+    /// ```
+    pub fn eq_to_resolution(&self, other: &DateTime, res: Resolution) -> bool {
+        DateTime::_truncate_naive(&self.dt.naive_utc(), &res) == DateTime::_truncate_naive(&other.dt.naive_utc(), &res)
+    }
+
+    /// Checks if the time represented by the `DateTime` object is in the half-open
+    /// range `[start, end)`. For other bounds, see `DateTime::is_between_bounds`.
     /// 
     /// ## Arguments
-    /// * `timestamp` A partial time string
+    /// * `start` - The inclusive start of the range
+    /// * `end` - The exclusive end of the range
     /// 
     /// ## Examples
     /// ```
-    /// // It is 2019-07-26 12:00
-    /// let dt: DateTime = DateTime::next_occurrence("15:00:00").unwrap();
-    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-07-26 15:00:00");
+    /// let start: DateTime = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+    /// let end: DateTime = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+    /// assert!(start.is_between(&start, &end));
+    /// assert!(!end.is_between(&start, &end));
+    /// ```
+    pub fn is_between(&self, start: &DateTime, end: &DateTime) -> bool {
+        self.is_between_bounds(start, end, true, false)
+    }
+
+    /// Checks if the time represented by the `DateTime` object is in a range with
+    /// explicitly inclusive or exclusive bounds. Returns false for an inverted range,
+    /// where the start is after the end.
     /// 
-    /// let dt: DateTime = DateTime::next_occurrence("10:00:00").unwrap();
-    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-07-27 10:00:00");
+    /// ## Arguments
+    /// * `start` - The start of the range
+    /// * `end` - The end of the range
+    /// * `start_inclusive` - Whether the start belongs to the range
+    /// * `end_inclusive` - Whether the end belongs to the range
     /// 
-    /// let dt: DateTime = DateTime::next_occurrence("1 15:00:00").unwrap();
-    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-08-01 15:00:00");
+    /// ## Examples
     /// ```
-    pub fn next_occurrence(timestamp: &str) -> Result<DateTime, Box<Error>> {
-        let dt = DateTime::now();
+    /// let start: DateTime = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+    /// let end: DateTime = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+    /// assert!(end.is_between_bounds(&start, &end, true, true));
+    /// assert!(!start.is_between_bounds(&start, &end, false, true));
+    /// ```
+    pub fn is_between_bounds(&self, start: &DateTime, end: &DateTime, start_inclusive: bool, end_inclusive: bool) -> bool {
+        if start.dt > end.dt {
+            return false;
+        }
 
-        DateTime::_next_occurrence(timestamp, &dt)
+        let after_start = if start_inclusive {self.dt >= start.dt} else {self.dt > start.dt};
+        let before_end = if end_inclusive {self.dt <= end.dt} else {self.dt < end.dt};
+        after_start && before_end
     }
 
-}
-
+    /// Calculates the elapsed fraction of a time window at the time represented by the
+    /// `DateTime` object. The result is clamped to [0.0, 1.0]. A zero-length or inverted
+    /// window is considered fully elapsed.
+    /// 
+    /// ## Arguments
+    /// * `start` - The start of the window
+    /// * `end` - The end of the window
+    /// 
+    /// ## Examples
+    /// ```
+    /// let start: DateTime = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+    /// let end: DateTime = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+    /// assert_eq!(dt.progress(&start, &end), 0.5);
+    /// ```
+    pub fn progress(&self, start: &DateTime, end: &DateTime) -> f64 {
+        let total = end.dt.signed_duration_since(start.dt).num_milliseconds();
+        if total <= 0 {
+            return 1.0;
+        }
+
+        let elapsed = self.dt.signed_duration_since(start.dt).num_milliseconds();
+        (elapsed as f64 / total as f64).clamp(0.0, 1.0)
+    }
+
+    /// Calculates the next occurrence of a partial time string, and creates a
+    /// `DateTime` object as a result. If it fails, it raises an error. Partial
+    /// times must be provided as strings in the general format %Y-%m-%d %H:%M:%S.
+    /// 
+    /// For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// ISO week patterns (e.g. `"W14"` or `"W14 08:30:00"`) occur on Monday of the week,
+    /// at the provided time of day or midnight (see `TimeFreq::parse_iso_week_pattern`).
+    /// 
+    /// Using it with days beyond 28, and with February 29 may cause unexpected results.
+    /// For more information, see the documentation of `DateTime::_add_months`.
+    /// 
+    /// ## Arguments
+    /// * `timestamp` A partial time string
+    /// 
+    /// ## Examples
+    /// ```
+    /// // It is 2019-07-26 12:00
+    /// let dt: DateTime = DateTime::next_occurrence("15:00:00").unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-07-26 15:00:00");
+    /// 
+    /// let dt: DateTime = DateTime::next_occurrence("10:00:00").unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-07-27 10:00:00");
+    /// 
+    /// let dt: DateTime = DateTime::next_occurrence("1 15:00:00").unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-08-01 15:00:00");
+    /// ```
+    pub fn next_occurrence(timestamp: &str) -> Result<DateTime, Box<Error>> {
+        let dt = DateTime::now();
+
+        DateTime::_next_occurrence(timestamp, &dt)
+    }
+
+    /// Calculates the first occurrence of a partial time string at or after a floor, and creates
+    /// a `DateTime` object as a result. If the floor is an occurrence itself, the floor is returned.
+    /// If it fails, it raises an error. For more information, see `DateTime::next_occurrence`.
+    /// 
+    /// ## Arguments
+    /// * `pattern` A partial time string
+    /// * `floor` The earliest acceptable occurrence
+    /// 
+    /// ## Examples
+    /// ```
+    /// let floor: DateTime = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+    /// let dt: DateTime = DateTime::next_occurrence_after("10:00:00", &floor).unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-07-27 10:00:00");
+    /// ```
+    pub fn next_occurrence_after(pattern: &str, floor: &DateTime) -> Result<DateTime, Box<Error>> {
+        DateTime::_next_occurrence(pattern, floor)
+    }
+
+    /// Utility method for checking if the next occurrence of a time pattern is due in the
+    /// second of a `DateTime` object. For more information, see `DateTime::occurrence_due`.
+    fn _occurrence_due(pattern: &str, ref_date: &DateTime) -> Result<bool, Box<Error>> {
+        let next = DateTime::_next_occurrence(pattern, ref_date)?;
+
+        Ok(next.to_epoch() == ref_date.to_epoch())
+    }
+
+    /// Checks if the next occurrence of a partial time string is due in the current second.
+    /// If it fails, it raises an error.
+    /// 
+    /// For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// 
+    /// ## Arguments
+    /// * `pattern` A partial time string
+    /// 
+    /// ## Examples
+    /// ```
+    /// // It is 2019-07-26 12:00:00
+    /// assert!(DateTime::occurrence_due("12:00:00").unwrap());
+    /// assert!(!DateTime::occurrence_due("15:00:00").unwrap());
+    /// ```
+    pub fn occurrence_due(pattern: &str) -> Result<bool, Box<Error>> {
+        let dt = DateTime::now();
+
+        DateTime::_occurrence_due(pattern, &dt)
+    }
+
+    /// Utility method for calculating the delay from a `DateTime` object until the next
+    /// occurrence of a time pattern. For more information, see `DateTime::sleep_until_next`.
+    fn _sleep_until_next(pattern: &str, ref_date: &DateTime) -> Result<std::time::Duration, Box<Error>> {
+        let next = DateTime::_next_occurrence(pattern, ref_date)?;
+
+        Ok(next.dt.signed_duration_since(ref_date.dt).to_std().unwrap_or(std::time::Duration::ZERO))
+    }
+
+    /// Calculates the delay until the next occurrence of a partial time string as a
+    /// `std::time::Duration`, which can be passed to `std::thread::sleep`. An occurrence,
+    /// which has just passed results in a zero delay. If it fails, it raises an error.
+    /// 
+    /// For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// 
+    /// ## Arguments
+    /// * `pattern` A partial time string
+    /// 
+    /// ## Examples
+    /// ```
+    /// std::thread::sleep(DateTime::sleep_until_next("00").unwrap());
+    /// ```
+    pub fn sleep_until_next(pattern: &str) -> Result<std::time::Duration, Box<Error>> {
+        let dt = DateTime::now();
+
+        DateTime::_sleep_until_next(pattern, &dt)
+    }
+
+    /// Utility method for calculating the time left in the current period of a fixed interval
+    /// at a `DateTime` object. For more information, see `DateTime::time_left_in_period`.
+    fn _time_left_in_period(pattern: &str, ref_date: &DateTime) -> Result<std::time::Duration, Box<Error>> {
+        let interval = TimeFreq::from_timestamp_strict(pattern, true)?;
+        if interval.years > 0 || interval.months > 0 {
+            return Err(PafError::create_error(&format!("Interval {} has no fixed length.", pattern)));
+        }
+
+        let secs = interval.calc_duration();
+        let boundary = Duration::try_seconds(secs)
+            .and_then(|period| ref_date.floor_to(secs).dt.checked_add_signed(period))
+            .ok_or_else(|| PafError::create_error(&format!("Interval {} is out of range.", pattern)))?;
+        Ok(boundary.signed_duration_since(ref_date.dt).to_std()?)
+    }
+
+    /// Calculates the time left from now until the end of the current period of a fixed
+    /// interval, where periods are aligned to the epoch (e.g. `"15:00"` gives the time left
+    /// until the next quarter hour). At a period boundary, the whole interval is left. The
+    /// pattern is read as a frequency, and it raises an error, if it is not valid, it is zero,
+    /// it contains months or years, as those have no fixed length, or the end of the period is
+    /// out of range.
+    /// 
+    /// ## Arguments
+    /// * `pattern` A partial time string used as a frequency
+    /// 
+    /// ## Examples
+    /// ```
+    /// // It is 2019-01-01 12:10:00
+    /// let left = DateTime::time_left_in_period("15:00").unwrap();
+    /// assert_eq!(left, std::time::Duration::from_secs(300));
+    /// ```
+    pub fn time_left_in_period(pattern: &str) -> Result<std::time::Duration, Box<Error>> {
+        let dt = DateTime::now();
+
+        DateTime::_time_left_in_period(pattern, &dt)
+    }
+
+    /// Returns the start of the unit represented by a resolution (e.g. the start of the day
+    /// for `Resolution::Day`), which contains the time of the `DateTime` object. If a timezone
+    /// is provided, the unit is treated as local. Raises an error, if the timezone is not valid,
+    /// or the local start does not exist.
+    /// 
+    /// ## Arguments
+    /// * `res` - The resolution of the unit
+    /// * `timezone` An optional timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:30:00", None).unwrap();
+    /// let start = dt.start_of(&Resolution::Day, None).unwrap();
+    /// assert_eq!(start.to_timestamp(None).unwrap(), "2019-01-01 00:00:00");
+    /// ```
+    pub fn start_of(&self, res: &Resolution, timezone: Option<&str>) -> Result<DateTime, Box<Error>> {
+        let tz: Tz = DateTime::_read_timezone(timezone)?;
+        let naive = DateTime::_truncate_naive(&self.dt.with_timezone(&tz).naive_local(), res);
+        let dt = tz.from_local_datetime(&naive).earliest()
+            .ok_or_else(|| PafError::create_error(&format!("Local time {} does not exist.", naive)))?;
+        Ok(DateTime {dt: dt.with_timezone(&Utc)})
+    }
+
+    /// Returns the `DateTime` object, if it is in the daily business window on a weekday,
+    /// otherwise, the next opening of the window. The window is given by an opening and a closing
+    /// time in UTC, where the opening is inclusive, and the closing is exclusive. Raises an error,
+    /// if the times are not valid, or the window is empty.
+    /// 
+    /// ## Arguments
+    /// * `open` - The opening time of the window (e.g. `"09:00:00"`)
+    /// * `close` - The closing time of the window (e.g. `"17:00:00"`)
+    /// 
+    /// ## Examples
+    /// ```
+    /// // A Saturday
+    /// let dt: DateTime = DateTime::from_timestamp("2019-07-27 12:00:00", None).unwrap();
+    /// let next = dt.next_business_time("09:00:00", "17:00:00").unwrap();
+    /// assert_eq!(next.to_timestamp(None).unwrap(), "2019-07-29 09:00:00");
+    /// ```
+    pub fn next_business_time(&self, open: &str, close: &str) -> Result<DateTime, Box<Error>> {
+        let open_secs = DateTime::_time_of_day_secs(open)?;
+        let close_secs = DateTime::_time_of_day_secs(close)?;
+        if open_secs >= close_secs {
+            return Err(PafError::create_error(&format!("Invalid business window {} - {}.", open, close)));
+        }
+
+        let mut day_start = self.start_of(&Resolution::Day, None)?;
+        // A weekday is found in at most 3 days
+        for _ in 0..4 {
+            let is_weekday = day_start.dt.weekday().num_days_from_monday() < 5;
+            let window_open = day_start.dt + Duration::seconds(open_secs);
+            let window_close = day_start.dt + Duration::seconds(close_secs);
+
+            if is_weekday && self.dt < window_close {
+                if self.dt >= window_open {
+                    return Ok(self.clone());
+                }
+                return Ok(DateTime {dt: window_open});
+            }
+            day_start.dt += Duration::days(1);
+        }
+        Err(PafError::create_error("Could not find the next business window."))
+    }
+
+    /// Utility method for parsing a time of day, and returning the seconds since midnight.
+    fn _time_of_day_secs(time: &str) -> Result<i64, Box<Error>> {
+        let parsed: TimeFreq = TimeFreq::from_timestamp(time, false)?;
+        if parsed.resolution > Resolution::Hour || parsed.calc_duration() >= 86_400 {
+            return Err(PafError::create_error(&format!("Invalid time of day {}.", time)));
+        }
+        Ok(parsed.calc_duration())
+    }
+
+    /// Returns the last second of the unit represented by a resolution (e.g. the end of the day
+    /// for `Resolution::Day`), which contains the time of the `DateTime` object. If a timezone
+    /// is provided, the unit is treated as local. Raises an error, if the timezone is not valid,
+    /// or the local end does not exist.
+    /// 
+    /// ## Arguments
+    /// * `res` - The resolution of the unit
+    /// * `timezone` An optional timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:30:00", None).unwrap();
+    /// let end = dt.end_of(&Resolution::Day, None).unwrap();
+    /// assert_eq!(end.to_timestamp(None).unwrap(), "2019-01-01 23:59:59");
+    /// ```
+    pub fn end_of(&self, res: &Resolution, timezone: Option<&str>) -> Result<DateTime, Box<Error>> {
+        let tz: Tz = DateTime::_read_timezone(timezone)?;
+        let start = DateTime::_truncate_naive(&self.dt.with_timezone(&tz).naive_local(), res);
+        let next_start = match res {
+            Resolution::Year => NaiveDate::from_ymd_opt(start.year() + 1, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+            Resolution::Month => NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+                .unwrap_or_else(|| NaiveDate::from_ymd_opt(start.year() + 1, 1, 1).unwrap()).and_hms_opt(0, 0, 0).unwrap(),
+            Resolution::Day => start + Duration::days(1),
+            Resolution::Hour => start + Duration::hours(1),
+            Resolution::Minute => start + Duration::minutes(1),
+            Resolution::Second | Resolution::None => start + Duration::seconds(1)
+        };
+        let naive = next_start - Duration::seconds(1);
+        let dt = tz.from_local_datetime(&naive).latest()
+            .ok_or_else(|| PafError::create_error(&format!("Local time {} does not exist.", naive)))?;
+        Ok(DateTime {dt: dt.with_timezone(&Utc)})
+    }
+
+    /// Creates an iterator over the occurrences of a partial time string at or after a
+    /// reference `DateTime` object. If the partial time string is not valid, it raises an error.
+    /// For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// 
+    /// The iterator is infinite for repeating patterns, and ends after a single occurrence
+    /// for non-repeating ones (e.g. full timestamps).
+    /// 
+    /// ## Arguments
+    /// * `timestamp` A partial time string
+    /// * `from` The reference `DateTime` object
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 10:30:00", None).unwrap();
+    /// let next_three: Vec<DateTime> = DateTime::occurrences("00:00", &dt).unwrap().take(3).collect();
+    /// assert_eq!(next_three[0].to_timestamp(None).unwrap(), "2019-01-01 11:00:00");
+    /// ```
+    pub fn occurrences(timestamp: &str, from: &DateTime) -> Result<Occurrences, Box<Error>> {
+        TimeFreq::from_timestamp(timestamp, false)?;
+        Ok(Occurrences {
+            pattern: timestamp.to_string(),
+            next_ref: Some(from.clone())
+        })
+    }
+
+    /// Calculates the occurrences of several partial time strings in a window, where both ends
+    /// are inclusive. Occurrences are merged into a single sorted list, and occurrences less than
+    /// a second apart are kept only once. If any of the patterns is not valid, it raises an error.
+    /// 
+    /// ## Arguments
+    /// * `patterns` The partial time strings
+    /// * `start` The start of the window
+    /// * `end` The end of the window
+    /// 
+    /// ## Examples
+    /// ```
+    /// let start: DateTime = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+    /// let end: DateTime = DateTime::from_timestamp("2019-01-01 23:59:59", None).unwrap();
+    /// let merged = DateTime::merged_occurrences(&["00:00", "12:00:00"], &start, &end).unwrap();
+    /// assert_eq!(merged.len(), 24);
+    /// ```
+    pub fn merged_occurrences(patterns: &[&str], start: &DateTime, end: &DateTime) -> Result<Vec<DateTime>, Box<Error>> {
+        let mut merged: Vec<DateTime> = Vec::new();
+        for pattern in patterns {
+            merged.extend(DateTime::occurrences(pattern, start)?.take_while(|occ| occ.dt <= end.dt));
+        }
+
+        merged.sort_by_key(|occ| occ.dt);
+        merged.dedup_by(|next, prev| next.dt - prev.dt < Duration::seconds(1));
+        Ok(merged)
+    }
+
+    /// Calculates every occurrence of a partial time string on the calendar day of a `DateTime`
+    /// object. If a timezone is provided, the day is treated as local. If it fails, it raises
+    /// an error. For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// 
+    /// ## Arguments
+    /// * `pattern` A partial time string
+    /// * `day` Any time on the requested day
+    /// * `timezone` An optional timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let day: DateTime = DateTime::from_timestamp("2019-01-01 10:30:00", None).unwrap();
+    /// let hourly = DateTime::occurrences_on_day("15:00", &day, None).unwrap();
+    /// assert_eq!(hourly.len(), 24);
+    /// ```
+    pub fn occurrences_on_day(pattern: &str, day: &DateTime, timezone: Option<&str>) -> Result<Vec<DateTime>, Box<Error>> {
+        let start = day.start_of(&Resolution::Day, timezone)?;
+        let end = day.end_of(&Resolution::Day, timezone)?;
+
+        Ok(DateTime::occurrences(pattern, &start)?.take_while(|occ| occ.dt <= end.dt).collect())
+    }
+
+    /// Utility method for collecting at most `n` occurrences of a partial time string at or
+    /// after a `DateTime` object. For more information, see `DateTime::next_n_occurrences`.
+    fn _next_n_occurrences(pattern: &str, n: usize, ref_date: &DateTime) -> Result<Vec<DateTime>, Box<Error>> {
+        Ok(DateTime::occurrences(pattern, ref_date)?.take(n).collect())
+    }
+
+    /// Calculates at most `n` upcoming occurrences of a partial time string from now. Fewer
+    /// occurrences are returned for non-repeating patterns (e.g. full timestamps), which
+    /// occur at most once. If the partial time string is not valid, it raises an error.
+    /// For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// 
+    /// ## Arguments
+    /// * `pattern` A partial time string
+    /// * `n` The maximum number of occurrences
+    /// 
+    /// ## Examples
+    /// ```
+    /// for occ in DateTime::next_n_occurrences("00:00", 5).unwrap() {
+    ///     println!("{}", occ.to_timestamp(None).unwrap());
+    /// }
+    /// ```
+    pub fn next_n_occurrences(pattern: &str, n: usize) -> Result<Vec<DateTime>, Box<Error>> {
+        let dt = DateTime::now();
+
+        DateTime::_next_n_occurrences(pattern, n, &dt)
+    }
+
+    /// Creates an infinite iterator walking time in fixed steps from a `DateTime` object. The
+    /// iterator yields `start`, `start + step`, `start + 2 * step`, and so on. Steps are added
+    /// with `DateTime::add`, therefore, month and year steps follow its wrapping rules. If the
+    /// step is invalid or zero, it raises an error.
+    /// 
+    /// ## Arguments
+    /// * `start` The first yielded time
+    /// * `step` A partial time string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let start: DateTime = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+    /// let steps: Vec<DateTime> = DateTime::step_iter(start, "15:00").unwrap().take(4).collect();
+    /// assert_eq!(steps[3].to_timestamp(None).unwrap(), "2019-01-01 12:45:00");
+    /// ```
+    pub fn step_iter(start: DateTime, step: &str) -> Result<impl Iterator<Item = DateTime>, Box<Error>> {
+        TimeFreq::from_timestamp_strict(step, true)?;
+        let step = step.to_string();
+        Ok(iter::successors(Some(start), move |prev| {
+            let mut next = prev.clone();
+            next.add(&step).ok()?;
+            Some(next)
+        }))
+    }
+
+    /// Validates a partial time string, and describes when it occurs in a human readable
+    /// sentence (e.g. `"Every day at 09:30:00"`). If the pattern is invalid, it raises an error.
+    /// 
+    /// For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// 
+    /// ## Arguments
+    /// * `pattern` A partial time string
+    /// 
+    /// ## Examples
+    /// ```
+    /// assert_eq!(DateTime::describe_pattern("9:30:0").unwrap(), "Every day at 09:30:00");
+    /// assert_eq!(DateTime::describe_pattern("15:00").unwrap(), "Every hour at 15:00");
+    /// ```
+    pub fn describe_pattern(pattern: &str) -> Result<String, Box<Error>> {
+        let tf: TimeFreq = TimeFreq::from_timestamp(pattern, false)?;
+        if tf.hours > 23 || tf.minutes > 59 || tf.seconds > 59
+            || (tf.resolution >= Resolution::Day && !(1..=31).contains(&tf.days))
+            || (tf.resolution >= Resolution::Month && !(1..=12).contains(&tf.months)) {
+            return Err(PafError::create_error(&format!("Invalid pattern {}.", pattern)));
+        }
+
+        let time = format!("{:02}:{:02}:{:02}", tf.hours, tf.minutes, tf.seconds);
+        Ok(match tf.resolution {
+            Resolution::Year => format!("Once on {}-{:02}-{:02} at {}", tf.years, tf.months, tf.days, time),
+            Resolution::Month => format!("Every year on {:02}-{:02} at {}", tf.months, tf.days, time),
+            Resolution::Day => format!("Every month on day {} at {}", tf.days, time),
+            Resolution::Hour => format!("Every day at {}", time),
+            Resolution::Minute => format!("Every hour at {:02}:{:02}", tf.minutes, tf.seconds),
+            Resolution::Second | Resolution::None => format!("Every minute at second {:02}", tf.seconds)
+        })
+    }
+
+    /// Utility function for rendering the magnitude of a duration in its largest whole unit
+    /// (e.g. `"5 minutes"` or `"1 day"`).
+    fn _humanize(duration: Duration) -> String {
+        let secs = duration.num_seconds().abs();
+        let (count, unit) = if secs >= 86_400 {
+            (secs / 86_400, "day")
+        } else if secs >= 3600 {
+            (secs / 3600, "hour")
+        } else if secs >= 60 {
+            (secs / 60, "minute")
+        } else {
+            (secs, "second")
+        };
+        format!("{} {}{}", count, unit, if count == 1 {""} else {"s"})
+    }
+
+    /// Utility method for describing the time of the `DateTime` object relative to another
+    /// one. For more information, see `DateTime::relative_to_now`.
+    fn _relative_to(&self, ref_date: &DateTime) -> String {
+        let diff = self.dt.signed_duration_since(ref_date.dt);
+        if diff.num_seconds().abs() < 2 {
+            "just now".to_string()
+        } else if diff > Duration::zero() {
+            format!("in {}", DateTime::_humanize(diff))
+        } else {
+            format!("{} ago", DateTime::_humanize(diff))
+        }
+    }
+
+    /// Describes the time of the `DateTime` object relative to now in its largest whole unit
+    /// (e.g. `"in 5 minutes"` or `"3 hours ago"`). Differences under 2 seconds are described
+    /// as `"just now"`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let mut dt: DateTime = DateTime::now();
+    /// dt.subtract("3:00:00").unwrap();
+    /// assert_eq!(dt.relative_to_now(), "3 hours ago");
+    /// ```
+    pub fn relative_to_now(&self) -> String {
+        self._relative_to(&DateTime::now())
+    }
+
+    /// Counts the occurrences of a partial time string between two `DateTime` objects (both
+    /// inclusive). Patterns with a fixed cycle (e.g. hourly or daily) are counted arithmetically,
+    /// while calendar dependent ones (e.g. monthly or yearly) are iterated. If it fails, or the
+    /// pattern does not repeat, it raises an error.
+    /// 
+    /// For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// 
+    /// ## Arguments
+    /// * `pattern` A partial time string
+    /// * `start` The start of the window
+    /// * `end` The end of the window
+    /// 
+    /// ## Examples
+    /// ```
+    /// let start: DateTime = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+    /// let end: DateTime = DateTime::from_timestamp("2019-01-01 23:59:59", None).unwrap();
+    /// assert_eq!(DateTime::count_occurrences("30:00", &start, &end).unwrap(), 24);
+    /// ```
+    pub fn count_occurrences(pattern: &str, start: &DateTime, end: &DateTime) -> Result<usize, Box<Error>> {
+        let parsed: TimeFreq = TimeFreq::from_timestamp(pattern, false)?;
+        let cycle = match parsed.cycle_length() {
+            Some(cycle) if cycle.resolution != Resolution::None => cycle,
+            _ => return Err(PafError::create_error("Too specific timestamp, occurrences do not repeat."))
+        };
+        let cycle_secs = if cycle.years == 0 && cycle.months == 0 { Some(cycle.calc_duration()) } else { None };
+
+        if let Some(cycle) = cycle_secs {
+            let first = DateTime::_next_occurrence(pattern, start)?;
+            if first.dt > end.dt {
+                Ok(0)
+            } else {
+                Ok(((end.to_epoch() - first.to_epoch()) / cycle + 1) as usize)
+            }
+        } else {
+            Ok(DateTime::occurrences(pattern, start)?.take_while(|occ| occ.dt <= end.dt).count())
+        }
+    }
+
+    /// Utility method for calculating the next occurrence of a time pattern, which does not fall
+    /// on a holiday, relative to a `DateTime` object. For more information, see
+    /// `DateTime::next_occurrence_skipping`.
+    fn _next_occurrence_skipping(timestamp: &str, holidays: &[DateTime], ref_date: &DateTime) -> Result<DateTime, Box<Error>> {
+        let mut holiday_starts = Vec::with_capacity(holidays.len());
+        for holiday in holidays {
+            holiday_starts.push(holiday.start_of(&Resolution::Day, None)?.dt);
+        }
+
+        for occurrence in DateTime::occurrences(timestamp, ref_date)? {
+            let day_start = occurrence.start_of(&Resolution::Day, None)?.dt;
+            if !holiday_starts.contains(&day_start) {
+                return Ok(occurrence);
+            }
+        }
+
+        // Only non-repeating patterns can run out of occurrences
+        Err(PafError::create_error("Too specific timestamp, there is no next occurrence outside holidays."))
+    }
+
+    /// Calculates the next occurrence of a partial time string, which does not fall on a holiday.
+    /// Holidays are compared by their calendar day in UTC, therefore, occurrences are skipped until
+    /// one is found on a non-holiday. If it fails, it raises an error.
+    /// 
+    /// For the exact rules of partial time strings, see the `TimeFreq` documentation.
+    /// 
+    /// ## Arguments
+    /// * `pattern` A partial time string
+    /// * `holidays` Any time on the holidays
+    /// 
+    /// ## Examples
+    /// ```
+    /// // It is 2019-12-24 12:00
+    /// let holidays = vec![DateTime::from_date(2019, 12, 25, None).unwrap()];
+    /// let dt: DateTime = DateTime::next_occurrence_skipping("10:00:00", &holidays).unwrap();
+    /// assert_eq!(dt.to_timestamp(None).unwrap(), "2019-12-26 10:00:00");
+    /// ```
+    pub fn next_occurrence_skipping(pattern: &str, holidays: &[DateTime]) -> Result<DateTime, Box<Error>> {
+        let dt = DateTime::now();
+
+        DateTime::_next_occurrence_skipping(pattern, holidays, &dt)
+    }
+
+    /// Merges overlapping or touching time windows in place, leaving a minimal set of disjoint
+    /// windows sorted by their start. Windows are represented as `(start, end)` pairs.
+    /// 
+    /// ## Arguments
+    /// * `windows` - The time windows to merge
+    /// 
+    /// ## Examples
+    /// ```
+    /// let mut windows = vec![
+    ///     (DateTime::from_timestamp("2019-01-01 10:00:00", None).unwrap(),
+    ///      DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap()),
+    ///     (DateTime::from_timestamp("2019-01-01 11:00:00", None).unwrap(),
+    ///      DateTime::from_timestamp("2019-01-01 13:00:00", None).unwrap())
+    /// ];
+    /// DateTime::merge_windows(&mut windows);
+    /// assert_eq!(windows.len(), 1);
+    /// assert_eq!(windows[0].1.to_timestamp(None).unwrap(), "2019-01-01 13:00:00");
+    /// ```
+    pub fn merge_windows(windows: &mut Vec<(DateTime, DateTime)>) {
+        windows.sort_by_key(|w| w.0.dt);
+
+        let mut merged: Vec<(DateTime, DateTime)> = Vec::with_capacity(windows.len());
+        for (start, end) in windows.drain(..) {
+            if let Some(last) = merged.last_mut() {
+                if start.dt <= last.1.dt {
+                    if end.dt > last.1.dt {
+                        last.1 = end;
+                    }
+                    continue;
+                }
+            }
+            merged.push((start, end));
+        }
+
+        *windows = merged;
+    }
+
+    /// Calculates the total time covered by time windows. Overlapping windows are merged,
+    /// therefore, overlaps are counted only once. Windows starting after their end are ignored.
+    /// 
+    /// ## Arguments
+    /// * `windows` - The time windows as `(start, end)` pairs
+    /// 
+    /// ## Examples
+    /// ```
+    /// let windows = vec![
+    ///     (DateTime::from_timestamp("2019-01-01 10:00:00", None).unwrap(),
+    ///      DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap()),
+    ///     (DateTime::from_timestamp("2019-01-01 11:00:00", None).unwrap(),
+    ///      DateTime::from_timestamp("2019-01-01 13:00:00", None).unwrap())
+    /// ];
+    /// assert_eq!(DateTime::total_coverage(&windows), Duration::hours(3));
+    /// ```
+    pub fn total_coverage(windows: &[(DateTime, DateTime)]) -> Duration {
+        let mut valid: Vec<(DateTime, DateTime)> = windows.iter()
+            .filter(|w| w.0.dt <= w.1.dt)
+            .map(|w| (w.0.clone(), w.1.clone()))
+            .collect();
+        DateTime::merge_windows(&mut valid);
+
+        valid.iter().fold(Duration::zero(), |total, w| total + (w.1.dt - w.0.dt))
+    }
+
+    /// Finds the first time window in a search range, when every server is free, and which is
+    /// at least `min_len` long. Servers are busy in their own `(start, end)` windows, therefore,
+    /// the common free time is the part of the range not covered by any busy window. Windows
+    /// starting after their end are ignored. Returns `None`, if there is no such window.
+    /// 
+    /// ## Arguments
+    /// * `busy_per_server` - The busy windows of every server
+    /// * `search_start` - The start of the search range
+    /// * `search_end` - The end of the search range
+    /// * `min_len` - The minimum length of the free window
+    /// 
+    /// ## Examples
+    /// ```
+    /// let busy = vec![
+    ///     vec![(DateTime::from_timestamp("2019-01-01 10:00:00", None).unwrap(),
+    ///           DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap())]
+    /// ];
+    /// let start = DateTime::from_timestamp("2019-01-01 09:00:00", None).unwrap();
+    /// let end = DateTime::from_timestamp("2019-01-01 18:00:00", None).unwrap();
+    /// let (free, _) = DateTime::common_free_window(&busy, &start, &end, Duration::hours(2)).unwrap();
+    /// assert_eq!(free.to_timestamp(None).unwrap(), "2019-01-01 12:00:00");
+    /// ```
+    pub fn common_free_window(busy_per_server: &[Vec<(DateTime, DateTime)>], search_start: &DateTime,
+        search_end: &DateTime, min_len: Duration) -> Option<(DateTime, DateTime)> {
+        let mut busy: Vec<(DateTime, DateTime)> = busy_per_server.iter()
+            .flatten()
+            .filter(|w| w.0.dt <= w.1.dt && w.1.dt > search_start.dt && w.0.dt < search_end.dt)
+            .map(|w| (w.0.clone(), w.1.clone()))
+            .collect();
+        DateTime::merge_windows(&mut busy);
+
+        let mut free_start = search_start.clone();
+        for (start, end) in busy.into_iter().chain(iter::once((search_end.clone(), search_end.clone()))) {
+            if start.dt > free_start.dt && start.dt - free_start.dt >= min_len {
+                return Some((free_start, start));
+            }
+            if end.dt > free_start.dt {
+                free_start = end;
+            }
+        }
+        None
+    }
+
+    /// Calculates the drift of an actual fire time from the expected one. The result is
+    /// signed: positive if the actual time is late, and negative if it is early.
+    /// 
+    /// ## Arguments
+    /// * `expected` - The scheduled fire time
+    /// * `actual` - The actual fire time
+    /// 
+    /// ## Examples
+    /// ```
+    /// let expected = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+    /// let actual = DateTime::from_timestamp("2019-01-01 12:00:30", None).unwrap();
+    /// assert_eq!(DateTime::drift(&expected, &actual), Duration::seconds(30));
+    /// ```
+    pub fn drift(expected: &DateTime, actual: &DateTime) -> Duration {
+        actual.dt - expected.dt
+    }
+
+    /// Checks if an actual fire time is within a tolerance of the expected one in either
+    /// direction. The sign of the tolerance is ignored. For the sign convention, see `DateTime::drift`.
+    /// 
+    /// ## Arguments
+    /// * `expected` - The scheduled fire time
+    /// * `actual` - The actual fire time
+    /// * `tol` - The tolerated drift
+    /// 
+    /// ## Examples
+    /// ```
+    /// let expected = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+    /// let actual = DateTime::from_timestamp("2019-01-01 11:59:30", None).unwrap();
+    /// assert!(DateTime::within_tolerance(&expected, &actual, Duration::minutes(1)));
+    /// ```
+    pub fn within_tolerance(expected: &DateTime, actual: &DateTime, tol: Duration) -> bool {
+        let drift = DateTime::drift(expected, actual);
+        let tol = if tol < Duration::zero() {-tol} else {tol};
+        -tol <= drift && drift <= tol
+    }
+
+    /// Checks if a timezone string is accepted by the `DateTime` methods, including
+    /// the shorthand `GMT+X` and `GMT-X` forms.
+    /// 
+    /// ## Arguments
+    /// * `tz` - A timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// assert!(DateTime::is_valid_timezone("CET"));
+    /// assert!(!DateTime::is_valid_timezone("Nowhere"));
+    /// ```
+    pub fn is_valid_timezone(tz: &str) -> bool {
+        DateTime::_read_timezone(Some(tz)).is_ok()
+    }
+
+    /// Returns every timezone string accepted by the `DateTime` methods. The list contains
+    /// the names in the IANA database, and the shorthand `GMT+X` and `GMT-X` forms of the
+    /// `Etc/GMT+X` and `Etc/GMT-X` timezones.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let timezones = DateTime::supported_timezones();
+    /// assert!(timezones.contains(&"CET"));
+    /// ```
+    pub fn supported_timezones() -> Vec<&'static str> {
+        let mut timezones: Vec<&'static str> = TZ_VARIANTS.iter().map(|tz| tz.name()).collect();
+        let shorthands: Vec<&'static str> = timezones.iter()
+            .filter(|name| name.starts_with("Etc/GMT+") || name.starts_with("Etc/GMT-"))
+            .map(|name| name.trim_start_matches("Etc/"))
+            .collect();
+
+        timezones.extend(shorthands);
+        timezones
+    }
+
+    /// Returns every timezone of the IANA database with its UTC offset (e.g. `+0100`) at the
+    /// provided instant, reflecting daylight saving time. Returns an empty list, if the instant
+    /// is out of the supported range.
+    /// 
+    /// ## Arguments
+    /// * `epoch` - A UNIX timestamp in seconds
+    /// 
+    /// ## Examples
+    /// ```
+    /// let offsets = DateTime::zone_offsets_at(1561982400);
+    /// assert!(offsets.contains(&("CET", "+0200".to_string())));
+    /// ```
+    pub fn zone_offsets_at(epoch: i64) -> Vec<(&'static str, String)> {
+        match Utc.timestamp_opt(epoch, 0).single() {
+            Some(instant) => TZ_VARIANTS.iter()
+                .map(|tz| (tz.name(), instant.with_timezone(tz).format("%z").to_string()))
+                .collect(),
+            None => Vec::new()
+        }
+    }
+
+    /// Checks if the time represented by the `DateTime` object is in daylight
+    /// saving time in the provided timezone. Raises an error, if the timezone
+    /// is not valid.
+    /// 
+    /// ## Arguments
+    /// * `timezone` A timezone string
+    /// 
+    /// ## Examples
+    /// ```
+    /// let dt: DateTime = DateTime::from_timestamp("2019-07-01 12:00:00", None).unwrap();
+    /// assert!(dt.is_dst("CET").unwrap());
+    /// 
+    /// let dt: DateTime = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+    /// assert!(!dt.is_dst("CET").unwrap());
+    /// ```
+    pub fn is_dst(&self, timezone: &str) -> Result<bool, Box<Error>> {
+        let tz: Tz = DateTime::_read_timezone(Some(timezone))?;
+        let offset = tz.offset_from_utc_datetime(&self.dt.naive_utc());
+
+        // The DST offset is the difference from the zone's standard offset
+        Ok(offset.dst_offset() != Duration::zero())
+    }
+}
+
+/// An iterator over the occurrences of a partial time string.
+/// Created by `DateTime::occurrences`.
+pub struct Occurrences {
+    pattern: String,
+    next_ref: Option<DateTime>
+}
+
+impl Iterator for Occurrences {
+    type Item = DateTime;
+
+    fn next(&mut self) -> Option<DateTime> {
+        let ref_date = self.next_ref.take()?;
+
+        // Non-repeating patterns raise an error once they are passed, which ends the iteration
+        let occurrence = DateTime::_next_occurrence(&self.pattern, &ref_date).ok()?;
+
+        // Occurrences at the reference time are included, so search from the next second
+        self.next_ref = Some(DateTime {dt: occurrence.dt + Duration::seconds(1)});
+        Some(occurrence)
+    }
+}
+
+/// A guard for firing partial time strings at most once per occurrence, when they are
+/// checked periodically (e.g. on every tick of a scheduler).
+#[derive(Default)]
+pub struct FireTracker {
+    // The next occurrence to fire for every pattern, or None, if a non-repeating pattern has fired
+    pending: HashMap<String, Option<DateTime>>
+}
+
+impl FireTracker {
+    /// Creates a new `FireTracker` without any recorded patterns.
+    pub fn new() -> FireTracker {
+        Default::default()
+    }
+
+    /// Checks if a pattern should fire at a tick. Returns true, if an occurrence of the pattern
+    /// has been reached since the previous call with the same pattern, and false otherwise. If
+    /// several occurrences have been crossed between two calls, the pattern fires only once.
+    /// On the first call with a pattern, it fires only, if the tick is exactly an occurrence.
+    /// Non-repeating patterns fire once. If the pattern is not valid, or it has no occurrence
+    /// on the first call, it raises an error.
+    /// 
+    /// ## Arguments
+    /// * `pattern` - A partial time string
+    /// * `now` - The time of the tick
+    /// 
+    /// ## Examples
+    /// ```
+    /// let mut tracker = FireTracker::new();
+    /// let tick = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+    /// assert!(tracker.should_fire("00", &tick).unwrap());
+    /// assert!(!tracker.should_fire("00", &tick).unwrap());
+    /// ```
+    pub fn should_fire(&mut self, pattern: &str, now: &DateTime) -> Result<bool, Box<Error>> {
+        let pending = match self.pending.get(pattern) {
+            Some(Some(pending)) => pending.clone(),
+            Some(None) => return Ok(false),
+            None => DateTime::_next_occurrence(pattern, now)?
+        };
+
+        let fire = now.dt >= pending.dt;
+        let next = if fire {
+            // Arm for the first occurrence after this tick, skipping every crossed one
+            DateTime::_next_occurrence(pattern, &DateTime {dt: now.dt + Duration::seconds(1)}).ok()
+        } else {
+            Some(pending)
+        };
+        self.pending.insert(pattern.to_string(), next);
+        Ok(fire)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::DateTime;
+
+    /// Creates a time window from two UTC timestamps.
+    fn window(start: &str, end: &str) -> (DateTime, DateTime) {
+        (DateTime::from_timestamp(start, None).unwrap(), DateTime::from_timestamp(end, None).unwrap())
+    }
+
     mod epoch {
         use super::super::*;
 
         #[test]
-        fn reads_from_epoch() {
-            let timeobj = DateTime::from_epoch(1_500_000_000);
-            assert_eq!(timeobj.to_epoch(), 1_500_000_000);
+        fn reads_from_epoch() {
+            let timeobj = DateTime::from_epoch(1_500_000_000);
+            assert_eq!(timeobj.to_epoch(), 1_500_000_000);
+        }
+
+        #[test]
+        fn writes_to_epoch() {
+            let timeobj = DateTime::from_timestamp("2017-07-14 02:40:00", None).unwrap();
+            assert_eq!(timeobj.to_epoch(), 1_500_000_000);
+        }
+    }
+    
+    mod from_timestamp {
+        use super::super::*;
+
+        #[test]
+        fn reads_from_timestamp() {
+            let timeobj = DateTime::from_timestamp("2017-07-14 02:40:00", None).unwrap();
+            assert_eq!(timeobj.to_epoch(), 1_500_000_000);
+        }
+
+        #[test]
+        fn invalid_tz_throws_error() {
+            let timeobj = DateTime::from_timestamp("2017-07-14 02:40:00", Some("Invalid"));
+            assert!(timeobj.is_err());
+        }
+    }
+    
+    mod from_timestamp_local {
+        use super::super::*;
+
+        #[test]
+        fn applies_host_offset() {
+            let ts = "2019-01-01 12:00:00";
+            let local = DateTime::from_timestamp_local(ts).unwrap();
+            let utc = DateTime::from_timestamp(ts, None).unwrap();
+            let offset = Local.offset_from_utc_datetime(&local.dt.naive_utc()).local_minus_utc() as i64;
+            assert_eq!(local.to_epoch() + offset, utc.to_epoch());
+        }
+
+        #[test]
+        fn differs_from_utc_on_non_utc_host() {
+            let ts = "2019-07-01 12:00:00";
+            let utc = DateTime::from_timestamp(ts, None).unwrap();
+            if Local.offset_from_utc_datetime(&utc.dt.naive_utc()).local_minus_utc() == 0 {
+                // The host is in UTC (or an equivalent zone), nothing to compare
+                return;
+            }
+            assert!(DateTime::from_timestamp_local(ts).unwrap().to_epoch() != utc.to_epoch());
+        }
+
+        #[test]
+        fn throws_error_with_invalid_timestamp() {
+            assert!(DateTime::from_timestamp_local("2019-01-01").is_err());
+        }
+    }
+
+    mod now {
+        use super::super::*;
+
+        #[test]
+        fn creates_from_current_time() {
+            let timeobj = DateTime::now();
+            assert!(timeobj.to_epoch() == Utc::now().timestamp())
+        }
+    }
+
+    mod to_timestamp {
+        use super::super::*;
+
+        #[test]
+        fn converts_to_timestamp() {
+            let timeobj = DateTime::from_epoch(1_500_000_000);
+            let timestamp = timeobj.to_timestamp(None).unwrap();
+            assert_eq!(timestamp, "2017-07-14 02:40:00");
+        }
+
+        #[test]
+        fn handles_timezones_in_timestamp() {
+            let timeobj = DateTime::from_timestamp("2017-07-14 02:40:00", Some("CET")).unwrap();
+            let timestamp = timeobj.to_timestamp(None).unwrap();
+            assert_eq!(timestamp, "2017-07-14 00:40:00");
+        }
+
+        #[test]
+        fn handles_daylight_savings() {
+            let timeobj = DateTime::from_timestamp("2017-03-14 02:40:00", Some("CET")).unwrap();
+            let timestamp = timeobj.to_timestamp(None).unwrap();
+            assert_eq!(timestamp, "2017-03-14 01:40:00");
+        }
+
+        #[test]
+        fn converts_timestamp_to_timezone() {
+            let timeobj = DateTime::from_epoch(1_500_000_000);
+            let timestamp = timeobj.to_timestamp(Some("CET")).unwrap();
+            assert_eq!(timestamp, "2017-07-14 04:40:00");
+        }
+
+        #[test]
+        fn invalid_tz_throws_error() {
+            let timeobj = DateTime::from_epoch(1_500_000_000);
+            let timestamp = timeobj.to_timestamp(Some("Invalid"));
+            assert!(timestamp.is_err());
+        }
+    }
+
+    mod _read_timezone {
+        use super::super::*;
+
+        #[test]
+        fn reads_timezone() {
+            let tz = DateTime::_read_timezone(Some("CET")).unwrap();
+            let dt = tz.timestamp(1_500_000_000, 0);
+            assert_eq!(dt.format("%z").to_string(), "+0200");
+        }
+
+        #[test]
+        fn defaults_to_utc() {
+            let tz = DateTime::_read_timezone(None).unwrap();
+            let dt = tz.timestamp(1_500_000_000, 0);
+            assert_eq!(dt.format("%z").to_string(), "+0000");
+        }
+
+        #[test]
+        fn works_with_offsetted_gmt() {
+            let mut tz = DateTime::_read_timezone(Some("GMT+2")).unwrap();
+            let mut dt = tz.timestamp(1_500_000_000, 0);
+            assert_eq!(dt.format("%z").to_string(), "-0200");
+
+            tz = DateTime::_read_timezone(Some("Etc/GMT-2")).unwrap();
+            dt = tz.timestamp(1_500_000_000, 0);
+            assert_eq!(dt.format("%z").to_string(), "+0200");
+        }
+
+        #[test]
+        fn works_with_hyphenated_names() {
+            let tz = DateTime::_read_timezone(Some("America/Port-au-Prince"));
+            assert!(tz.is_ok());
+        }
+
+        #[test]
+        fn invalid_tz_throws_error() {
+            let tz = DateTime::_read_timezone(Some("Invalid"));
+            assert!(tz.is_err());
+        }
+    }
+
+    mod add {
+        use super::super::*;
+
+        #[test]
+        fn adds_duration_to_date() {
+            // 2017-07-14 02:40:00
+            let mut timeobj = DateTime::from_epoch(1_500_000_000);
+            timeobj.add("1-2-3 4:5:6").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-09-17 06:45:06");
+        }
+
+        #[test]
+        fn wraps_around_year() {
+            let mut timeobj = DateTime::from_epoch(1_500_000_000);
+            timeobj.add("15-0").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-10-14 02:40:00");
+
+            timeobj.add("30-0").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2021-04-14 02:40:00");
+
+            timeobj.add("9-0").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2022-01-14 02:40:00");
+
+            timeobj.add("20-0").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2023-09-14 02:40:00");
+        }
+
+        #[test]
+        fn adds_months_up_to_december() {
+            let mut timeobj = DateTime::from_timestamp("2019-11-01 00:00:00", None).unwrap();
+            timeobj.add("0-1-0 0:0:0").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-12-01 00:00:00");
+
+            timeobj.add("0-1-0 0:0:0").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2020-01-01 00:00:00");
+        }
+
+        #[test]
+        fn throws_error_on_invalid_ts() {
+            let mut timeobj = DateTime::from_epoch(1_500_000_000);
+            let res = timeobj.add("15?-0");
+            assert!(res.is_err());
+        }
+    }
+
+    mod subtract {
+        use super::super::*;
+
+        #[test]
+        fn subs_duration_from_date() {
+            // 2017-07-14 02:40:00
+            let mut timeobj = DateTime::from_epoch(1_500_000_000);
+            timeobj.subtract("1-2-3 4:5:6").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2016-05-10 22:34:54");
+        }
+
+        #[test]
+        fn wraps_around_year() {
+            let mut timeobj = DateTime::from_epoch(1_500_000_000);
+            timeobj.subtract("15-0").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2016-04-14 02:40:00");
+
+            timeobj.subtract("30-0").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2013-10-14 02:40:00");
+
+            timeobj.subtract("11-0").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2012-11-14 02:40:00");
+
+            timeobj.subtract("20-0").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2011-03-14 02:40:00");
+        }
+
+        #[test]
+        fn throws_error_on_invalid_ts() {
+            let mut timeobj = DateTime::from_epoch(1_500_000_000);
+            let res = timeobj.subtract("15?-0");
+            assert!(res.is_err());
+        }
+    }
+
+    mod is_passed {
+        use super::super::*;
+
+        #[test]
+        fn returns_false_if_not_passed() {
+            let timeobj = DateTime::from_timestamp("2222-02-02 22:22:22", None).unwrap();
+            assert!(!timeobj.is_passed(None));
+        }
+
+        #[test]
+        fn returns_true_if_passed() {
+            let timeobj = DateTime::from_timestamp("1972-02-02 22:22:22", None).unwrap();
+            assert!(timeobj.is_passed(None));
+        }
+    }
+
+    mod eq_to_resolution {
+        use super::super::*;
+
+        #[test]
+        fn ignores_smaller_components() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 12:30:05", None).unwrap();
+            let other = DateTime::from_timestamp("2019-01-01 12:30:55", None).unwrap();
+            assert!(timeobj.eq_to_resolution(&other, Resolution::Minute));
+            assert!(timeobj.eq_to_resolution(&other, Resolution::Day));
+            assert!(!timeobj.eq_to_resolution(&other, Resolution::Second));
+        }
+
+        #[test]
+        fn compares_larger_components() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 12:30:05", None).unwrap();
+            let other = DateTime::from_timestamp("2019-01-01 13:30:05", None).unwrap();
+            assert!(!timeobj.eq_to_resolution(&other, Resolution::Minute));
+            assert!(timeobj.eq_to_resolution(&other, Resolution::Day));
+        }
+    }
+
+    mod is_between_bounds {
+        use super::super::*;
+
+        #[test]
+        fn handles_closed_range() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+            assert!(start.is_between_bounds(&start, &end, true, true));
+            assert!(end.is_between_bounds(&start, &end, true, true));
+        }
+
+        #[test]
+        fn handles_half_open_ranges() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+            assert!(start.is_between_bounds(&start, &end, true, false));
+            assert!(!end.is_between_bounds(&start, &end, true, false));
+            assert!(!start.is_between_bounds(&start, &end, false, true));
+            assert!(end.is_between_bounds(&start, &end, false, true));
+        }
+
+        #[test]
+        fn handles_open_range() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+            let middle = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            assert!(!start.is_between_bounds(&start, &end, false, false));
+            assert!(!end.is_between_bounds(&start, &end, false, false));
+            assert!(middle.is_between_bounds(&start, &end, false, false));
+        }
+
+        #[test]
+        fn returns_false_for_inverted_range() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+            assert!(!start.is_between_bounds(&end, &start, true, true));
+            assert!(!end.is_between_bounds(&end, &start, true, true));
+        }
+
+        #[test]
+        fn is_half_open_by_default() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+            assert!(start.is_between(&start, &end));
+            assert!(!end.is_between(&start, &end));
+        }
+    }
+
+    mod progress {
+        use super::super::*;
+
+        #[test]
+        fn is_zero_at_start() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+            assert_eq!(start.progress(&start, &end), 0.0);
+            let before = DateTime::from_timestamp("2018-12-31 00:00:00", None).unwrap();
+            assert_eq!(before.progress(&start, &end), 0.0);
+        }
+
+        #[test]
+        fn is_half_at_midpoint() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+            let middle = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            assert_eq!(middle.progress(&start, &end), 0.5);
+        }
+
+        #[test]
+        fn is_one_past_end() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-02 00:00:00", None).unwrap();
+            assert_eq!(end.progress(&start, &end), 1.0);
+            let after = DateTime::from_timestamp("2019-01-03 00:00:00", None).unwrap();
+            assert_eq!(after.progress(&start, &end), 1.0);
+        }
+
+        #[test]
+        fn is_one_for_zero_length_window() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let before = DateTime::from_timestamp("2018-12-31 00:00:00", None).unwrap();
+            assert_eq!(start.progress(&start, &start), 1.0);
+            assert_eq!(before.progress(&start, &start), 1.0);
+        }
+    }
+
+    mod _merge_error {
+        use super::super::*;
+
+        #[test]
+        fn returns_result_if_not_null() {
+            let chrono_res = Utc::now().with_minute(40);
+            let merge_res = DateTime::_merge_error(chrono_res, String::from("Some error message."));
+            assert!(merge_res.is_ok())
+        }
+
+        #[test]
+        fn returns_error_if_null() {
+            let chrono_res = Utc::now().with_minute(70);
+            let merge_res = DateTime::_merge_error(chrono_res, String::from("Some error message."));
+            assert!(merge_res.is_err());
+
+            let err = merge_res.err().unwrap();
+            assert_eq!(*err.message, String::from("Some error message."));
+        }
+    }
+
+    mod _merge_timefreq {
+        use super::super::*;
+
+        #[test]
+        fn merges_seconds() {
+            let old_now = Utc::now();
+            let mut dt = DateTime::now();
+            let mut tf = TimeFreq::from_timestamp("10", true).unwrap();
+            dt._merge_timefreq(&mut tf).unwrap();
+
+            assert_eq!(dt.dt.second(), 10);
+            assert_eq!(dt.dt.minute(), old_now.minute());
+            assert_eq!(dt.dt.hour(), old_now.hour());
+            assert_eq!(dt.dt.day(), old_now.day());
+            assert_eq!(dt.dt.month(), old_now.month());
+            assert_eq!(dt.dt.year(), old_now.year());
+        }
+
+        #[test]
+        fn merges_minutes() {
+            let old_now = Utc::now();
+            let mut dt = DateTime::now();
+            let mut tf = TimeFreq::from_timestamp("20:0", true).unwrap();
+            dt._merge_timefreq(&mut tf).unwrap();
+
+            assert_eq!(dt.dt.second(), 0);
+            assert_eq!(dt.dt.minute(), 20);
+            assert_eq!(dt.dt.hour(), old_now.hour());
+            assert_eq!(dt.dt.day(), old_now.day());
+            assert_eq!(dt.dt.month(), old_now.month());
+            assert_eq!(dt.dt.year(), old_now.year());
+        }
+
+        #[test]
+        fn merges_hours() {
+            let old_now = Utc::now();
+            let mut dt = DateTime::now();
+            let mut tf = TimeFreq::from_timestamp("12:0:0", true).unwrap();
+            dt._merge_timefreq(&mut tf).unwrap();
+
+            assert_eq!(dt.dt.second(), 0);
+            assert_eq!(dt.dt.minute(), 0);
+            assert_eq!(dt.dt.hour(), 12);
+            assert_eq!(dt.dt.day(), old_now.day());
+            assert_eq!(dt.dt.month(), old_now.month());
+            assert_eq!(dt.dt.year(), old_now.year());
+        }
+
+        #[test]
+        fn merges_days() {
+            let old_now = Utc::now();
+            let mut dt = DateTime::now();
+            let mut tf = TimeFreq::from_timestamp("28 0:0:0", true).unwrap();
+            dt._merge_timefreq(&mut tf).unwrap();
+
+            assert_eq!(dt.dt.second(), 0);
+            assert_eq!(dt.dt.minute(), 0);
+            assert_eq!(dt.dt.hour(), 0);
+            assert_eq!(dt.dt.day(), 28);
+            assert_eq!(dt.dt.month(), old_now.month());
+            assert_eq!(dt.dt.year(), old_now.year());
+        }
+
+        #[test]
+        fn merges_months() {
+            let old_now = Utc::now();
+            let mut dt = DateTime::now();
+            let mut tf = TimeFreq::from_timestamp("9-1 0:0:0", true).unwrap();
+            dt._merge_timefreq(&mut tf).unwrap();
+
+            assert_eq!(dt.dt.second(), 0);
+            assert_eq!(dt.dt.minute(), 0);
+            assert_eq!(dt.dt.hour(), 0);
+            assert_eq!(dt.dt.day(), 1);
+            assert_eq!(dt.dt.month(), 9);
+            assert_eq!(dt.dt.year(), old_now.year());
+        }
+
+        #[test]
+        fn merges_years() {
+            let mut dt = DateTime::now();
+            let mut tf = TimeFreq::from_timestamp("1001-1-1 0:0:0", true).unwrap();
+            dt._merge_timefreq(&mut tf).unwrap();
+
+            assert_eq!(dt.dt.second(), 0);
+            assert_eq!(dt.dt.minute(), 0);
+            assert_eq!(dt.dt.hour(), 0);
+            assert_eq!(dt.dt.day(), 1);
+            assert_eq!(dt.dt.month(), 1);
+            assert_eq!(dt.dt.year(), 1001);
+        }
+
+        #[test]
+        fn returns_error_if_cannot_be_merged() {
+            let mut dt = DateTime::now();
+            let mut tf = TimeFreq::from_timestamp("25:70:90", true).unwrap();
+            let mut res = dt._merge_timefreq(&mut tf);
+            assert!(res.is_err());
+
+            //Day cannot be 0, only if it is not part of the relative date
+            tf = TimeFreq::from_timestamp("0-1-0 0:0:0", true).unwrap();
+            res = dt._merge_timefreq(&mut tf);
+            assert!(res.is_err());
+
+            //Month cannot be 0, only if it is not part of the relative date
+            tf = TimeFreq::from_timestamp("1001-0-1 0:0:0", true).unwrap();
+            res = dt._merge_timefreq(&mut tf);
+            assert!(res.is_err());
+        }
+    }
+
+    mod next_occurrence {
+        use super::super::*;
+
+        #[test]
+        fn uses_current_time_as_reference() {
+            // Will fail at every exact hour, but currently IDC
+            let mut now = Utc::now();
+            let min = now.minute() - 1;
+            let mut dt = DateTime::next_occurrence(&format!("{}:00", min)).unwrap();
+            let mut expected = format!("{}-{:0width$}-{:0width$} {:0width$}:{:0width$}:{}",
+                now.year(), now.month(), now.day(), now.hour() + 1, min, "00", width = 2);
+
+            assert_eq!(dt.to_timestamp(None).unwrap(), expected);
+
+            // TODO: test if it fails between midnight and 1am
+            now = Utc::now();
+            let hr;
+            let mut exp_dt;
+            if now.hour() == 0 {
+                exp_dt = DateTime::now();
+                hr = 1;
+            } else {
+                exp_dt = DateTime::now();
+                exp_dt.add("1 00:00:00").unwrap();
+                hr = now.hour() - 1;
+            }
+            dt = DateTime::next_occurrence(&format!("{}:00:00", hr)).unwrap();
+            expected = format!("{}-{:0width$}-{:0width$} {:0width$}:{:0width$}:{}",
+                exp_dt.dt.year(), exp_dt.dt.month(), exp_dt.dt.day(), hr, "00", "00", width = 2);
+            assert_eq!(dt.to_timestamp(None).unwrap(), expected);
+        }
+    }
+
+    mod next_occurrence_after {
+        use super::super::*;
+
+        #[test]
+        fn uses_floor_as_reference() {
+            let floor = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            let mut timeobj = DateTime::next_occurrence_after("15:00:00", &floor).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-26 15:00:00");
+
+            timeobj = DateTime::next_occurrence_after("10:00:00", &floor).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-27 10:00:00");
+        }
+
+        #[test]
+        fn returns_floor_if_it_is_an_occurrence() {
+            let floor = DateTime::from_timestamp("2019-07-26 10:00:00", None).unwrap();
+            let timeobj = DateTime::next_occurrence_after("10:00:00", &floor).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-26 10:00:00");
+        }
+
+        #[test]
+        fn throws_error_if_there_is_no_occurrence() {
+            let floor = DateTime::from_timestamp("2019-07-26 10:00:00", None).unwrap();
+            assert!(DateTime::next_occurrence_after("2019-01-01 00:00:00", &floor).is_err());
+        }
+    }
+
+    mod _next_occurrence {
+        use super::super::*;
+
+        #[test]
+        fn correct_in_common_cases() {
+            let mut dt = DateTime::from_timestamp("2019-01-01 10:00:05", None).unwrap();
+            let mut next_occur = DateTime::_next_occurrence("04", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-01-01 10:01:04");
+
+            dt = DateTime::from_timestamp("2019-01-01 10:05:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("04:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-01-01 11:04:00");
+
+            dt = DateTime::from_timestamp("2019-01-01 10:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("09:02:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-01-02 09:02:00");
+
+            dt = DateTime::from_timestamp("2019-01-01 11:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("10:02:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-01-02 10:02:00");
+
+            dt = DateTime::from_timestamp("2019-01-01 10:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("00:00:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-01-02 00:00:00");
+
+            dt = DateTime::from_timestamp("2019-01-12 10:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("07 00:00:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-02-07 00:00:00");
+
+            dt = DateTime::from_timestamp("2019-03-12 10:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("02-01 00:00:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2020-02-01 00:00:00");
+
+            // Neccessary for defining one-shots in an unusual way
+            dt = DateTime::from_timestamp("2019-03-12 10:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("2020-01-01 00:00:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2020-01-01 00:00:00");
+        }
+
+        #[test]
+        fn handles_edge_cases() {
+            let mut dt = DateTime::from_timestamp("2019-01-31 10:00:00", None).unwrap();
+            let mut next_occur = DateTime::_next_occurrence("31 00:00:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-03-03 00:00:00");
+
+            dt = DateTime::from_timestamp("2019-07-31 12:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("31 11:30:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-08-31 11:30:00");
+
+            dt = DateTime::from_timestamp("2019-02-15 12:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("30 11:30:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-03-02 11:30:00");
+
+            dt = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("02-29 11:30:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-03-01 11:30:00");
+        }
+
+        #[test]
+        fn schedules_iso_weeks() {
+            let mut dt = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            let mut next_occur = DateTime::_next_occurrence("W14", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-04-01 00:00:00");
+
+            dt = DateTime::from_timestamp("2019-04-01 09:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("W14 08:30:00", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2020-03-30 08:30:00");
+
+            dt = DateTime::from_timestamp("2019-12-31 12:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("W1", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2021-01-04 00:00:00");
+
+            dt = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            next_occur = DateTime::_next_occurrence("W53", &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2020-12-28 00:00:00");
+        }
+
+        #[test]
+        fn throws_error_on_invalid_iso_week() {
+            let dt = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            assert!(DateTime::_next_occurrence("W54", &dt).is_err());
+            assert!(DateTime::_next_occurrence("W0 08:00:00", &dt).is_err());
+        }
+    }
+
+    mod _add_months {
+        use super::super::*;
+
+        #[test]
+        fn handles_normal_cases() {
+            let mut timeobj = DateTime::from_timestamp("2018-08-15 10:30:00", None).unwrap();
+            timeobj._add_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-09-15 10:30:00");
+
+            timeobj._add_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-10-15 10:30:00");
+
+            timeobj._add_months(2);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-12-15 10:30:00");
+        }
+
+        #[test]
+        fn handles_edge_cases() {
+            let mut timeobj = DateTime::from_timestamp("2018-03-31 10:30:00", None).unwrap();
+            timeobj._add_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-05-01 10:30:00");
+
+            timeobj = DateTime::from_timestamp("2018-01-31 10:30:00", None).unwrap();
+            timeobj._add_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-03-03 10:30:00");
+
+            timeobj = DateTime::from_timestamp("2018-01-30 10:30:00", None).unwrap();
+            timeobj._add_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-03-02 10:30:00");
+
+            timeobj = DateTime::from_timestamp("2018-01-29 10:30:00", None).unwrap();
+            timeobj._add_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-03-01 10:30:00");
+        }
+    }
+
+    mod _sub_months {
+        use super::super::*;
+
+        #[test]
+        fn handles_normal_cases() {
+            let mut timeobj = DateTime::from_timestamp("2018-12-15 10:30:00", None).unwrap();
+            timeobj._sub_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-11-15 10:30:00");
+
+            timeobj._sub_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-10-15 10:30:00");
+
+            timeobj._sub_months(2);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-08-15 10:30:00");
+        }
+
+        #[test]
+        fn handles_edge_cases() {
+            let mut timeobj = DateTime::from_timestamp("2018-05-31 10:30:00", None).unwrap();
+            timeobj._sub_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-04-30 10:30:00");
+
+            timeobj = DateTime::from_timestamp("2018-03-31 10:30:00", None).unwrap();
+            timeobj._sub_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-02-28 10:30:00");
+
+            timeobj = DateTime::from_timestamp("2018-03-30 10:30:00", None).unwrap();
+            timeobj._sub_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-02-27 10:30:00");
+
+            timeobj = DateTime::from_timestamp("2018-03-29 10:30:00", None).unwrap();
+            timeobj._sub_months(1);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-02-26 10:30:00");
+        }
+    }
+
+    mod is_dst {
+        use super::super::*;
+
+        #[test]
+        fn returns_true_in_summer() {
+            let timeobj = DateTime::from_timestamp("2019-07-01 12:00:00", None).unwrap();
+            assert!(timeobj.is_dst("CET").unwrap());
+        }
+
+        #[test]
+        fn returns_false_in_winter() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            assert!(!timeobj.is_dst("CET").unwrap());
+            assert!(!timeobj.is_dst("UTC").unwrap());
+        }
+
+        #[test]
+        fn invalid_tz_throws_error() {
+            let timeobj = DateTime::from_epoch(1_500_000_000);
+            assert!(timeobj.is_dst("Invalid").is_err());
+        }
+    }
+
+    mod format {
+        use super::super::*;
+
+        #[test]
+        fn formats_with_custom_format() {
+            let timeobj = DateTime::from_epoch(1_500_000_000);
+            assert_eq!(timeobj.format("%Y-%m-%d", None).unwrap(), "2017-07-14");
+            assert_eq!(timeobj.format("%H:%M", Some("CET")).unwrap(), "04:40");
+        }
+
+        #[test]
+        fn throws_error_on_invalid_format() {
+            assert!(DateTime::from_epoch(0).format("%Q", None).is_err());
+        }
+    }
+
+    mod shift_to_offset {
+        use super::super::*;
+
+        #[test]
+        fn shifts_to_half_hour_offset() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 20:00:00", None).unwrap();
+            assert_eq!(timeobj.shift_to_offset(5 * 3600 + 30 * 60).unwrap(), "2019-01-02 01:30:00");
+        }
+
+        #[test]
+        fn shifts_to_negative_offset() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 05:00:00", None).unwrap();
+            assert_eq!(timeobj.shift_to_offset(-8 * 3600).unwrap(), "2018-12-31 21:00:00");
+        }
+
+        #[test]
+        fn throws_error_on_out_of_range_offset() {
+            assert!(DateTime::from_epoch(0).shift_to_offset(86_400).is_err());
+            assert!(DateTime::from_epoch(0).shift_to_offset(-86_400).is_err());
+            assert!(DateTime::from_epoch(0).shift_to_offset(i32::MIN).is_err());
+        }
+    }
+
+    mod local_midnight {
+        use super::super::*;
+
+        #[test]
+        fn snaps_to_local_midnight_in_summer() {
+            let epoch = DateTime::from_timestamp("2019-07-15 12:00:00", None).unwrap().to_epoch();
+            let timeobj = DateTime::local_midnight(epoch, "CET").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-14 22:00:00");
+
+            // Already the next day in local time
+            let epoch = DateTime::from_timestamp("2019-07-14 23:30:00", None).unwrap().to_epoch();
+            let timeobj = DateTime::local_midnight(epoch, "CET").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-14 22:00:00");
+        }
+
+        #[test]
+        fn snaps_to_local_midnight_in_winter() {
+            let epoch = DateTime::from_timestamp("2019-01-15 12:00:00", None).unwrap().to_epoch();
+            let timeobj = DateTime::local_midnight(epoch, "CET").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-01-14 23:00:00");
+        }
+
+        #[test]
+        fn falls_back_if_midnight_does_not_exist() {
+            // Clocks jumped from 00:00 to 01:00 in Sao Paulo on 2018-11-04
+            let epoch = DateTime::from_timestamp("2018-11-04 12:00:00", None).unwrap().to_epoch();
+            let timeobj = DateTime::local_midnight(epoch, "America/Sao_Paulo").unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-11-04 03:00:00");
+        }
+
+        #[test]
+        fn throws_error_on_invalid_input() {
+            assert!(DateTime::local_midnight(0, "Mars/Olympus").is_err());
+            assert!(DateTime::local_midnight(i64::MAX, "CET").is_err());
+        }
+    }
+
+    mod set_default_format {
+        use super::super::*;
+
+        #[test]
+        fn changes_and_restores_default() {
+            let timeobj = DateTime::from_epoch(1_500_000_000);
+
+            DateTime::set_default_format("%Y-%m-%dT%H:%M:%S").unwrap();
+            let iso = timeobj.to_timestamp(None).unwrap();
+            let parsed = DateTime::from_timestamp("2017-07-14T02:40:00", None).map(|dt| dt.to_epoch());
+            DateTime::reset_default_format();
+
+            assert_eq!(iso, "2017-07-14T02:40:00");
+            assert_eq!(parsed.unwrap(), 1_500_000_000);
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2017-07-14 02:40:00");
+        }
+
+        #[test]
+        fn keeps_canonical_format_internally() {
+            DateTime::set_default_format("%Y-%m-%dT%H:%M:%S").unwrap();
+            let truncated = DateTime::from_timestamp_with_resolution("2019-01-01", None).map(|(dt, res)| (dt.to_epoch(), res));
+            let json = DateTime::from_epoch(1_500_000_000).to_json_value();
+            DateTime::reset_default_format();
+
+            let (epoch, res) = truncated.unwrap();
+            assert_eq!(epoch, 1_546_300_800);
+            assert!(res == Resolution::Day);
+            assert_eq!(json, Value::String(String::from("2017-07-14 02:40:00")));
+        }
+
+        #[test]
+        fn round_trips_json_across_formats() {
+            DateTime::set_default_format("%Y-%m-%dT%H:%M:%S").unwrap();
+            let json = DateTime::from_epoch(1_500_000_000).to_json_value();
+            DateTime::reset_default_format();
+            assert_eq!(DateTime::from_json_value(&json).unwrap().to_epoch(), 1_500_000_000);
+
+            let json = DateTime::from_epoch(1_500_000_000).to_json_value();
+            DateTime::set_default_format("%Y-%m-%dT%H:%M:%S").unwrap();
+            let parsed = DateTime::from_json_value(&json).map(|dt| dt.to_epoch());
+            DateTime::reset_default_format();
+            assert_eq!(parsed.unwrap(), 1_500_000_000);
+        }
+
+        #[test]
+        fn rejects_invalid_format() {
+            assert!(DateTime::set_default_format("%Y-%Q").is_err());
+            assert_eq!(DateTime::from_epoch(0).to_timestamp(None).unwrap(), "1970-01-01 00:00:00");
+        }
+    }
+
+    mod format_epochs {
+        use super::super::*;
+
+        #[test]
+        fn formats_in_timezone() {
+            let stamps = DateTime::format_epochs(&[0, 1_500_000_000, 1_546_300_800], Some("CET"), None).unwrap();
+            assert_eq!(stamps, vec!["1970-01-01 01:00:00", "2017-07-14 04:40:00", "2019-01-01 01:00:00"]);
+        }
+
+        #[test]
+        fn uses_custom_format() {
+            let stamps = DateTime::format_epochs(&[0, 1_500_000_000], None, Some("%Y-%m-%d")).unwrap();
+            assert_eq!(stamps, vec!["1970-01-01", "2017-07-14"]);
+        }
+
+        #[test]
+        fn throws_error_on_invalid_input() {
+            assert!(DateTime::format_epochs(&[0], Some("Mars/Olympus"), None).is_err());
+            assert!(DateTime::format_epochs(&[0, i64::MAX], None, None).is_err());
+        }
+
+        #[test]
+        fn throws_error_on_invalid_format() {
+            assert!(DateTime::format_epochs(&[0], None, Some("%Q")).is_err());
+        }
+    }
+
+    mod from_date {
+        use super::super::*;
+
+        #[test]
+        fn creates_at_midnight() {
+            let timeobj = DateTime::from_date(2017, 7, 14, None).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2017-07-14 00:00:00");
+        }
+
+        #[test]
+        fn handles_timezones() {
+            let timeobj = DateTime::from_date(2017, 7, 14, Some("CET")).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2017-07-13 22:00:00");
+            assert_eq!(timeobj.to_timestamp(Some("CET")).unwrap(), "2017-07-14 00:00:00");
+        }
+
+        #[test]
+        fn throws_error_on_invalid_date() {
+            assert!(DateTime::from_date(2019, 2, 30, None).is_err());
+            assert!(DateTime::from_date(2019, 13, 1, None).is_err());
+        }
+    }
+
+    mod start_of {
+        use super::super::*;
+
+        #[test]
+        fn truncates_to_resolution() {
+            let timeobj = DateTime::from_timestamp("2019-05-17 10:30:15", None).unwrap();
+            assert_eq!(timeobj.start_of(&Resolution::Year, None).unwrap().to_timestamp(None).unwrap(), "2019-01-01 00:00:00");
+            assert_eq!(timeobj.start_of(&Resolution::Month, None).unwrap().to_timestamp(None).unwrap(), "2019-05-01 00:00:00");
+            assert_eq!(timeobj.start_of(&Resolution::Day, None).unwrap().to_timestamp(None).unwrap(), "2019-05-17 00:00:00");
+            assert_eq!(timeobj.start_of(&Resolution::Hour, None).unwrap().to_timestamp(None).unwrap(), "2019-05-17 10:00:00");
+            assert_eq!(timeobj.start_of(&Resolution::Minute, None).unwrap().to_timestamp(None).unwrap(), "2019-05-17 10:30:00");
+        }
+
+        #[test]
+        fn handles_timezones() {
+            let timeobj = DateTime::from_timestamp("2019-05-17 23:30:00", None).unwrap();
+            let start = timeobj.start_of(&Resolution::Day, Some("CET")).unwrap();
+            assert_eq!(start.to_timestamp(Some("CET")).unwrap(), "2019-05-18 00:00:00");
+        }
+    }
+
+    mod end_of {
+        use super::super::*;
+
+        #[test]
+        fn returns_last_second_of_unit() {
+            let timeobj = DateTime::from_timestamp("2019-02-17 10:30:15", None).unwrap();
+            assert_eq!(timeobj.end_of(&Resolution::Year, None).unwrap().to_timestamp(None).unwrap(), "2019-12-31 23:59:59");
+            assert_eq!(timeobj.end_of(&Resolution::Month, None).unwrap().to_timestamp(None).unwrap(), "2019-02-28 23:59:59");
+            assert_eq!(timeobj.end_of(&Resolution::Day, None).unwrap().to_timestamp(None).unwrap(), "2019-02-17 23:59:59");
+            assert_eq!(timeobj.end_of(&Resolution::Hour, None).unwrap().to_timestamp(None).unwrap(), "2019-02-17 10:59:59");
+        }
+
+        #[test]
+        fn wraps_around_year() {
+            let timeobj = DateTime::from_timestamp("2019-12-17 10:30:15", None).unwrap();
+            assert_eq!(timeobj.end_of(&Resolution::Month, None).unwrap().to_timestamp(None).unwrap(), "2019-12-31 23:59:59");
+        }
+    }
+
+    mod occurrences {
+        use super::super::*;
+
+        #[test]
+        fn iterates_over_occurrences() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 10:30:00", None).unwrap();
+            let occs: Vec<DateTime> = DateTime::occurrences("00:00", &timeobj).unwrap().take(3).collect();
+
+            assert_eq!(occs[0].to_timestamp(None).unwrap(), "2019-01-01 11:00:00");
+            assert_eq!(occs[1].to_timestamp(None).unwrap(), "2019-01-01 12:00:00");
+            assert_eq!(occs[2].to_timestamp(None).unwrap(), "2019-01-01 13:00:00");
+        }
+
+        #[test]
+        fn includes_reference_time() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 11:00:00", None).unwrap();
+            let first = DateTime::occurrences("00:00", &timeobj).unwrap().next().unwrap();
+            assert_eq!(first.to_timestamp(None).unwrap(), "2019-01-01 11:00:00");
+        }
+
+        #[test]
+        fn ends_for_non_repeating_patterns() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 11:00:00", None).unwrap();
+            let occs: Vec<DateTime> = DateTime::occurrences("2020-01-01 00:00:00", &timeobj).unwrap().collect();
+            assert_eq!(occs.len(), 1);
+        }
+
+        #[test]
+        fn throws_error_on_invalid_pattern() {
+            let timeobj = DateTime::from_epoch(1_500_000_000);
+            assert!(DateTime::occurrences("invalid", &timeobj).is_err());
+        }
+    }
+
+    mod _next_n_occurrences {
+        use super::super::*;
+
+        #[test]
+        fn returns_n_hourly_occurrences() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 10:30:00", None).unwrap();
+            let occs = DateTime::_next_n_occurrences("00:00", 5, &timeobj).unwrap();
+            assert_eq!(occs.len(), 5);
+            assert_eq!(occs[0].to_timestamp(None).unwrap(), "2019-01-01 11:00:00");
+            assert_eq!(occs[4].to_timestamp(None).unwrap(), "2019-01-01 15:00:00");
+        }
+
+        #[test]
+        fn stops_for_one_shot_pattern() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 10:30:00", None).unwrap();
+            let occs = DateTime::_next_n_occurrences("2019-06-01 00:00:00", 5, &timeobj).unwrap();
+            assert_eq!(occs.len(), 1);
+            assert_eq!(occs[0].to_timestamp(None).unwrap(), "2019-06-01 00:00:00");
+        }
+
+        #[test]
+        fn handles_zero_and_passed_patterns() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 10:30:00", None).unwrap();
+            assert!(DateTime::_next_n_occurrences("00:00", 0, &timeobj).unwrap().is_empty());
+            assert!(DateTime::_next_n_occurrences("2018-06-01 00:00:00", 5, &timeobj).unwrap().is_empty());
+            assert!(DateTime::_next_n_occurrences("soon", 5, &timeobj).is_err());
+        }
+    }
+
+    mod merged_occurrences {
+        use super::super::*;
+
+        #[test]
+        fn merges_overlapping_patterns() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-01 02:00:00", None).unwrap();
+            let merged = DateTime::merged_occurrences(&["30:00", "00:00"], &start, &end).unwrap();
+
+            let stamps: Vec<String> = merged.iter().map(|dt| dt.to_timestamp(None).unwrap()).collect();
+            assert_eq!(stamps, vec![
+                "2019-01-01 00:00:00", "2019-01-01 00:30:00", "2019-01-01 01:00:00",
+                "2019-01-01 01:30:00", "2019-01-01 02:00:00"
+            ]);
+        }
+
+        #[test]
+        fn removes_duplicates() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-01 23:59:59", None).unwrap();
+            let merged = DateTime::merged_occurrences(&["12:00:00", "00:00", "0:0"], &start, &end).unwrap();
+            assert_eq!(merged.len(), 24);
+        }
+
+        #[test]
+        fn throws_error_on_invalid_pattern() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            assert!(DateTime::merged_occurrences(&["00:00", "1:2:3:4"], &start, &start).is_err());
+        }
+    }
+
+    mod occurrences_on_day {
+        use super::super::*;
+
+        #[test]
+        fn returns_every_occurrence() {
+            let day = DateTime::from_timestamp("2019-01-01 10:30:00", None).unwrap();
+            let occs = DateTime::occurrences_on_day("15:00", &day, None).unwrap();
+
+            assert_eq!(occs.len(), 24);
+            assert_eq!(occs[0].to_timestamp(None).unwrap(), "2019-01-01 00:15:00");
+            assert_eq!(occs[23].to_timestamp(None).unwrap(), "2019-01-01 23:15:00");
+        }
+
+        #[test]
+        fn returns_single_daily_occurrence() {
+            let day = DateTime::from_timestamp("2019-01-01 10:30:00", None).unwrap();
+            let occs = DateTime::occurrences_on_day("09:30:00", &day, None).unwrap();
+
+            assert_eq!(occs.len(), 1);
+            assert_eq!(occs[0].to_timestamp(None).unwrap(), "2019-01-01 09:30:00");
+        }
+
+        #[test]
+        fn uses_local_day() {
+            let day = DateTime::from_timestamp("2019-01-01 10:30:00", None).unwrap();
+            let occs = DateTime::occurrences_on_day("23:30:00", &day, Some("CET")).unwrap();
+
+            // Local day in CET is 2018-12-31 23:00:00 - 2019-01-01 22:59:59 in UTC
+            assert_eq!(occs.len(), 1);
+            assert_eq!(occs[0].to_timestamp(None).unwrap(), "2018-12-31 23:30:00");
+        }
+    }
+
+    mod _next_occurrence_skipping {
+        use super::super::*;
+
+        #[test]
+        fn skips_holidays() {
+            let dt = DateTime::from_timestamp("2019-12-24 12:00:00", None).unwrap();
+            let holidays = vec![
+                DateTime::from_date(2019, 12, 25, None).unwrap(),
+                DateTime::from_timestamp("2019-12-26 15:00:00", None).unwrap()
+            ];
+            let next_occur = DateTime::_next_occurrence_skipping("10:00:00", &holidays, &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-12-27 10:00:00");
         }
 
         #[test]
-        fn writes_to_epoch() {
-            let timeobj = DateTime::from_timestamp("2017-07-14 02:40:00", None).unwrap();
-            assert_eq!(timeobj.to_epoch(), 1_500_000_000);
+        fn keeps_non_holidays() {
+            let dt = DateTime::from_timestamp("2019-12-24 09:00:00", None).unwrap();
+            let holidays = vec![DateTime::from_date(2019, 12, 25, None).unwrap()];
+            let next_occur = DateTime::_next_occurrence_skipping("10:00:00", &holidays, &dt).unwrap();
+            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-12-24 10:00:00");
+        }
+
+        #[test]
+        fn throws_error_for_non_repeating_pattern_on_holiday() {
+            let dt = DateTime::from_timestamp("2019-12-24 09:00:00", None).unwrap();
+            let holidays = vec![DateTime::from_date(2019, 12, 25, None).unwrap()];
+            let next_occur = DateTime::_next_occurrence_skipping("2019-12-25 10:00:00", &holidays, &dt);
+            assert!(next_occur.is_err());
         }
     }
-    
-    mod from_timestamp {
+
+    mod supported_timezones {
         use super::super::*;
 
         #[test]
-        fn reads_from_timestamp() {
-            let timeobj = DateTime::from_timestamp("2017-07-14 02:40:00", None).unwrap();
-            assert_eq!(timeobj.to_epoch(), 1_500_000_000);
+        fn lists_timezones() {
+            let timezones = DateTime::supported_timezones();
+            assert!(!timezones.is_empty());
+            assert!(timezones.contains(&"UTC"));
+            assert!(timezones.contains(&"CET"));
+            assert!(timezones.contains(&"Etc/GMT+2"));
+            assert!(timezones.contains(&"GMT+2"));
         }
 
         #[test]
-        fn invalid_tz_throws_error() {
-            let timeobj = DateTime::from_timestamp("2017-07-14 02:40:00", Some("Invalid"));
-            assert!(timeobj.is_err());
+        fn lists_only_valid_timezones() {
+            for tz in DateTime::supported_timezones() {
+                assert!(DateTime::_read_timezone(Some(tz)).is_ok());
+            }
         }
     }
-    
-    mod now {
+
+    mod zone_offsets_at {
         use super::super::*;
 
         #[test]
-        fn creates_from_current_time() {
-            let timeobj = DateTime::now();
-            assert!(timeobj.to_epoch() == Utc::now().timestamp())
+        fn lists_offsets_in_winter() {
+            // 2019-01-01 12:00:00 UTC
+            let offsets = DateTime::zone_offsets_at(1546344000);
+            assert_eq!(offsets.len(), TZ_VARIANTS.len());
+            assert!(offsets.contains(&("UTC", "+0000".to_string())));
+            assert!(offsets.contains(&("CET", "+0100".to_string())));
+        }
+
+        #[test]
+        fn reflects_daylight_saving_time() {
+            // 2019-07-01 12:00:00 UTC
+            let offsets = DateTime::zone_offsets_at(1561982400);
+            assert!(offsets.contains(&("UTC", "+0000".to_string())));
+            assert!(offsets.contains(&("CET", "+0200".to_string())));
+            assert!(offsets.contains(&("Etc/GMT+2", "-0200".to_string())));
+        }
+
+        #[test]
+        fn returns_empty_list_out_of_range() {
+            assert!(DateTime::zone_offsets_at(i64::MAX).is_empty());
         }
     }
 
-    mod to_timestamp {
+    mod is_valid_timezone {
         use super::super::*;
 
         #[test]
-        fn converts_to_timestamp() {
-            let timeobj = DateTime::from_epoch(1_500_000_000);
-            let timestamp = timeobj.to_timestamp(None).unwrap();
-            assert_eq!(timestamp, "2017-07-14 02:40:00");
+        fn accepts_valid_timezones() {
+            assert!(DateTime::is_valid_timezone("CET"));
+            assert!(DateTime::is_valid_timezone("GMT+2"));
+            assert!(DateTime::is_valid_timezone("Europe/Budapest"));
         }
 
         #[test]
-        fn handles_timezones_in_timestamp() {
-            let timeobj = DateTime::from_timestamp("2017-07-14 02:40:00", Some("CET")).unwrap();
-            let timestamp = timeobj.to_timestamp(None).unwrap();
-            assert_eq!(timestamp, "2017-07-14 00:40:00");
+        fn rejects_invalid_timezones() {
+            assert!(!DateTime::is_valid_timezone("Nowhere"));
+            assert!(!DateTime::is_valid_timezone(""));
         }
+    }
+
+    mod floor_to {
+        use super::super::*;
 
         #[test]
-        fn handles_daylight_savings() {
-            let timeobj = DateTime::from_timestamp("2017-03-14 02:40:00", Some("CET")).unwrap();
-            let timestamp = timeobj.to_timestamp(None).unwrap();
-            assert_eq!(timestamp, "2017-03-14 01:40:00");
+        fn floors_to_interval() {
+            let below = DateTime::from_timestamp("2019-01-01 12:04:59", None).unwrap();
+            let at = DateTime::from_timestamp("2019-01-01 12:05:00", None).unwrap();
+            let above = DateTime::from_timestamp("2019-01-01 12:05:01", None).unwrap();
+
+            assert_eq!(below.floor_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:00:00");
+            assert_eq!(at.floor_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:05:00");
+            assert_eq!(above.floor_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:05:00");
         }
 
         #[test]
-        fn converts_timestamp_to_timezone() {
-            let timeobj = DateTime::from_epoch(1_500_000_000);
-            let timestamp = timeobj.to_timestamp(Some("CET")).unwrap();
-            assert_eq!(timestamp, "2017-07-14 04:40:00");
+        fn ignores_invalid_interval() {
+            let timeobj = DateTime::from_epoch(1_500_000_001);
+            assert_eq!(timeobj.floor_to(0).to_epoch(), 1_500_000_001);
+            assert_eq!(timeobj.floor_to(-300).to_epoch(), 1_500_000_001);
         }
 
         #[test]
-        fn invalid_tz_throws_error() {
-            let timeobj = DateTime::from_epoch(1_500_000_000);
-            let timestamp = timeobj.to_timestamp(Some("Invalid"));
-            assert!(timestamp.is_err());
+        fn saturates_out_of_range_results() {
+            let min = ChronoDateTime::<Utc>::MIN_UTC.timestamp();
+            assert_eq!(DateTime::from_epoch(-1).floor_to(i64::MAX).to_epoch(), min);
         }
     }
 
-    mod _read_timezone {
+    mod ceil_to {
         use super::super::*;
 
         #[test]
-        fn reads_timezone() {
-            let tz = DateTime::_read_timezone(Some("CET")).unwrap();
-            let dt = tz.timestamp(1_500_000_000, 0);
-            assert_eq!(dt.format("%z").to_string(), "+0200");
+        fn ceils_to_interval() {
+            let below = DateTime::from_timestamp("2019-01-01 12:04:59", None).unwrap();
+            let at = DateTime::from_timestamp("2019-01-01 12:05:00", None).unwrap();
+            let above = DateTime::from_timestamp("2019-01-01 12:05:01", None).unwrap();
+
+            assert_eq!(below.ceil_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:05:00");
+            assert_eq!(at.ceil_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:05:00");
+            assert_eq!(above.ceil_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:10:00");
         }
 
         #[test]
-        fn defaults_to_utc() {
-            let tz = DateTime::_read_timezone(None).unwrap();
-            let dt = tz.timestamp(1_500_000_000, 0);
-            assert_eq!(dt.format("%z").to_string(), "+0000");
+        fn ignores_invalid_interval() {
+            let timeobj = DateTime::from_epoch(1_500_000_001);
+            assert_eq!(timeobj.ceil_to(0).to_epoch(), 1_500_000_001);
         }
 
         #[test]
-        fn works_with_offsetted_gmt() {
-            let mut tz = DateTime::_read_timezone(Some("GMT+2")).unwrap();
-            let mut dt = tz.timestamp(1_500_000_000, 0);
-            assert_eq!(dt.format("%z").to_string(), "-0200");
+        fn saturates_out_of_range_results() {
+            let max = ChronoDateTime::<Utc>::MAX_UTC.timestamp();
+            assert_eq!(DateTime::from_epoch(1).ceil_to(i64::MAX).to_epoch(), max);
+            assert_eq!(DateTime::from_epoch(max).ceil_to(7 * 86_400).to_epoch(), max);
+        }
+    }
 
-            tz = DateTime::_read_timezone(Some("Etc/GMT-2")).unwrap();
-            dt = tz.timestamp(1_500_000_000, 0);
-            assert_eq!(dt.format("%z").to_string(), "+0200");
+    mod round_to {
+        use super::super::*;
+
+        #[test]
+        fn rounds_to_nearest_interval() {
+            let below = DateTime::from_timestamp("2019-01-01 12:04:59", None).unwrap();
+            let at = DateTime::from_timestamp("2019-01-01 12:05:00", None).unwrap();
+            let above = DateTime::from_timestamp("2019-01-01 12:05:01", None).unwrap();
+
+            assert_eq!(below.round_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:05:00");
+            assert_eq!(at.round_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:05:00");
+            assert_eq!(above.round_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:05:00");
         }
 
         #[test]
-        fn invalid_tz_throws_error() {
-            let tz = DateTime::_read_timezone(Some("Invalid"));
-            assert!(tz.is_err());
+        fn rounds_ties_up() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 12:02:30", None).unwrap();
+            assert_eq!(timeobj.round_to(300).to_timestamp(None).unwrap(), "2019-01-01 12:05:00");
+        }
+
+        #[test]
+        fn ignores_invalid_interval() {
+            let timeobj = DateTime::from_epoch(1_500_000_001);
+            assert_eq!(timeobj.round_to(0).to_epoch(), 1_500_000_001);
+            assert_eq!(timeobj.round_to(-300).to_epoch(), 1_500_000_001);
+        }
+
+        #[test]
+        fn saturates_out_of_range_results() {
+            let min = ChronoDateTime::<Utc>::MIN_UTC.timestamp();
+            let max = ChronoDateTime::<Utc>::MAX_UTC.timestamp();
+            assert_eq!(DateTime::from_epoch(min).round_to(1 - min).to_epoch(), min);
+            assert_eq!(DateTime::from_epoch(max).round_to(max + 1).to_epoch(), max);
+            assert_eq!(DateTime::from_epoch(1).round_to(i64::MAX).to_epoch(), 0);
         }
     }
 
-    mod add {
+    mod nth_weekday {
         use super::super::*;
 
         #[test]
-        fn adds_duration_to_date() {
-            // 2017-07-14 02:40:00
-            let mut timeobj = DateTime::from_epoch(1_500_000_000);
-            timeobj.add("1-2-3 4:5:6").unwrap();
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-09-17 06:45:06");
+        fn finds_nth_weekday() {
+            let mut timeobj = DateTime::nth_weekday(2019, 7, Weekday::Tue, 3, None).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-16 00:00:00");
+
+            timeobj = DateTime::nth_weekday(2019, 7, Weekday::Mon, 1, None).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-01 00:00:00");
+
+            timeobj = DateTime::nth_weekday(2019, 3, Weekday::Fri, 5, None).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-03-29 00:00:00");
         }
 
         #[test]
-        fn wraps_around_year() {
-            let mut timeobj = DateTime::from_epoch(1_500_000_000);
-            timeobj.add("15-0").unwrap();
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-10-14 02:40:00");
+        fn handles_timezones() {
+            let timeobj = DateTime::nth_weekday(2019, 7, Weekday::Tue, 3, Some("CET")).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-15 22:00:00");
+        }
 
-            timeobj.add("30-0").unwrap();
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2021-04-14 02:40:00");
+        #[test]
+        fn throws_error_if_no_such_weekday() {
+            assert!(DateTime::nth_weekday(2019, 7, Weekday::Fri, 5, None).is_err());
+            assert!(DateTime::nth_weekday(2019, 7, Weekday::Fri, 0, None).is_err());
+            assert!(DateTime::nth_weekday(2019, 7, Weekday::Fri, 6, None).is_err());
+            assert!(DateTime::nth_weekday(2019, 13, Weekday::Fri, 1, None).is_err());
+        }
+    }
 
-            timeobj.add("9-0").unwrap();
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2022-01-14 02:40:00");
+    mod iso_week {
+        use super::super::*;
 
-            timeobj.add("20-0").unwrap();
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2023-09-14 02:40:00");
+        #[test]
+        fn uses_iso_year_around_new_year() {
+            let timeobj = DateTime::from_timestamp("2021-01-01 00:00:00", None).unwrap();
+            assert_eq!(timeobj.iso_week(), (2020, 53, 5));
+
+            let timeobj = DateTime::from_timestamp("2019-12-30 00:00:00", None).unwrap();
+            assert_eq!(timeobj.iso_week(), (2020, 1, 1));
         }
 
         #[test]
-        fn throws_error_on_invalid_ts() {
-            let mut timeobj = DateTime::from_epoch(1_500_000_000);
-            let res = timeobj.add("15?-0");
-            assert!(res.is_err());
+        fn returns_mid_year_week() {
+            let timeobj = DateTime::from_timestamp("2019-07-14 23:59:59", None).unwrap();
+            assert_eq!(timeobj.iso_week(), (2019, 28, 7));
         }
     }
 
-    mod subtract {
+    mod from_iso_week {
         use super::super::*;
 
         #[test]
-        fn subs_duration_from_date() {
-            // 2017-07-14 02:40:00
-            let mut timeobj = DateTime::from_epoch(1_500_000_000);
-            timeobj.subtract("1-2-3 4:5:6").unwrap();
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2016-05-10 22:34:54");
+        fn finds_weekday_in_week() {
+            let mut timeobj = DateTime::from_iso_week(2019, 14, Weekday::Mon).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-04-01 00:00:00");
+
+            timeobj = DateTime::from_iso_week(2020, 1, Weekday::Mon).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-12-30 00:00:00");
+
+            timeobj = DateTime::from_iso_week(2020, 53, Weekday::Sun).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2021-01-03 00:00:00");
         }
 
         #[test]
-        fn wraps_around_year() {
-            let mut timeobj = DateTime::from_epoch(1_500_000_000);
-            timeobj.subtract("15-0").unwrap();
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2016-04-14 02:40:00");
+        fn round_trips_with_iso_week() {
+            let timeobj = DateTime::from_iso_week(2020, 53, Weekday::Sun).unwrap();
+            assert_eq!(timeobj.iso_week(), (2020, 53, 7));
+        }
 
-            timeobj.subtract("30-0").unwrap();
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2013-10-14 02:40:00");
+        #[test]
+        fn throws_error_on_invalid_week() {
+            assert!(DateTime::from_iso_week(2019, 0, Weekday::Mon).is_err());
+            assert!(DateTime::from_iso_week(2019, 54, Weekday::Mon).is_err());
+            assert!(DateTime::from_iso_week(2019, 53, Weekday::Mon).is_err());
+        }
+    }
 
-            timeobj.subtract("11-0").unwrap();
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2012-11-14 02:40:00");
+    mod _sleep_until_next {
+        use super::super::*;
 
-            timeobj.subtract("20-0").unwrap();
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2011-03-14 02:40:00");
+        #[test]
+        fn waits_for_future_occurrence() {
+            let now = DateTime::from_timestamp("2019-01-01 12:00:30", None).unwrap();
+            let delay = DateTime::_sleep_until_next("00", &now).unwrap();
+            assert_eq!(delay, std::time::Duration::from_secs(30));
         }
 
         #[test]
-        fn throws_error_on_invalid_ts() {
-            let mut timeobj = DateTime::from_epoch(1_500_000_000);
-            let res = timeobj.subtract("15?-0");
-            assert!(res.is_err());
+        fn returns_zero_for_current_occurrence() {
+            let now = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            let delay = DateTime::_sleep_until_next("00", &now).unwrap();
+            assert_eq!(delay, std::time::Duration::ZERO);
+        }
+
+        #[test]
+        fn throws_error_with_passed_pattern() {
+            let now = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            assert!(DateTime::_sleep_until_next("2018-01-01 00:00:00", &now).is_err());
         }
     }
 
-    mod is_passed {
+    mod _time_left_in_period {
         use super::super::*;
 
         #[test]
-        fn returns_false_if_not_passed() {
-            let timeobj = DateTime::from_timestamp("2222-02-02 22:22:22", None).unwrap();
-            assert!(!timeobj.is_passed(None));
+        fn calculates_time_left_in_quarter_hour() {
+            let mut now = DateTime::from_timestamp("2019-01-01 12:10:00", None).unwrap();
+            assert_eq!(DateTime::_time_left_in_period("15:00", &now).unwrap(), std::time::Duration::from_secs(300));
+            now = DateTime::from_timestamp("2019-01-01 12:14:59", None).unwrap();
+            assert_eq!(DateTime::_time_left_in_period("15:00", &now).unwrap(), std::time::Duration::from_secs(1));
+            now = DateTime::from_timestamp("2019-01-01 12:46:30", None).unwrap();
+            assert_eq!(DateTime::_time_left_in_period("15:00", &now).unwrap(), std::time::Duration::from_secs(810));
+        }
+
+        #[test]
+        fn returns_full_interval_at_boundary() {
+            let mut now = DateTime::from_timestamp("2019-01-01 12:15:00", None).unwrap();
+            assert_eq!(DateTime::_time_left_in_period("15:00", &now).unwrap(), std::time::Duration::from_secs(900));
+            now = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            assert_eq!(DateTime::_time_left_in_period("15:00", &now).unwrap(), std::time::Duration::from_secs(900));
+        }
+
+        #[test]
+        fn rejects_calendar_and_zero_intervals() {
+            let now = DateTime::from_timestamp("2019-01-01 12:10:00", None).unwrap();
+            assert!(DateTime::_time_left_in_period("1-0 0:0:0", &now).is_err());
+            assert!(DateTime::_time_left_in_period("1-0-0 0:0:0", &now).is_err());
+            assert!(DateTime::_time_left_in_period("0:0:0", &now).is_err());
+        }
+
+        #[test]
+        fn rejects_out_of_range_intervals() {
+            let now = DateTime::from_timestamp("2019-01-01 12:10:00", None).unwrap();
+            assert!(DateTime::_time_left_in_period("4000000000:0:0", &now).is_err());
+            assert!(DateTime::_time_left_in_period("4000000000 0:0:0", &now).is_err());
+        }
+    }
+
+    mod _occurrence_due {
+        use super::super::*;
+
+        #[test]
+        fn is_due_at_occurrence() {
+            let ref_date = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            assert!(DateTime::_occurrence_due("12:00:00", &ref_date).unwrap());
+            assert!(DateTime::_occurrence_due("00:00", &ref_date).unwrap());
+        }
+
+        #[test]
+        fn is_not_due_before_occurrence() {
+            let ref_date = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            assert!(!DateTime::_occurrence_due("15:00:00", &ref_date).unwrap());
+            assert!(!DateTime::_occurrence_due("11:59:59", &ref_date).unwrap());
+        }
+
+        #[test]
+        fn throws_error_with_invalid_pattern() {
+            let ref_date = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            assert!(DateTime::_occurrence_due("12:00:00:00", &ref_date).is_err());
+        }
+    }
+
+    mod merge_windows {
+        use super::super::*;
+        use super::window;
+
+        #[test]
+        fn merges_overlapping_windows() {
+            let mut windows = vec![
+                window("2019-01-01 11:00:00", "2019-01-01 13:00:00"),
+                window("2019-01-01 10:00:00", "2019-01-01 12:00:00")
+            ];
+            DateTime::merge_windows(&mut windows);
+            assert_eq!(windows.iter().map(|w| (w.0.to_timestamp(None).unwrap(), w.1.to_timestamp(None).unwrap())).collect::<Vec<_>>(), vec![
+                (String::from("2019-01-01 10:00:00"), String::from("2019-01-01 13:00:00"))
+            ]);
+        }
+
+        #[test]
+        fn merges_touching_windows() {
+            let mut windows = vec![
+                window("2019-01-01 10:00:00", "2019-01-01 12:00:00"),
+                window("2019-01-01 12:00:00", "2019-01-01 13:00:00")
+            ];
+            DateTime::merge_windows(&mut windows);
+            assert_eq!(windows.len(), 1);
+        }
+
+        #[test]
+        fn keeps_disjoint_windows() {
+            let mut windows = vec![
+                window("2019-01-01 14:00:00", "2019-01-01 15:00:00"),
+                window("2019-01-01 10:00:00", "2019-01-01 12:00:00")
+            ];
+            DateTime::merge_windows(&mut windows);
+            assert_eq!(windows.iter().map(|w| (w.0.to_timestamp(None).unwrap(), w.1.to_timestamp(None).unwrap())).collect::<Vec<_>>(), vec![
+                (String::from("2019-01-01 10:00:00"), String::from("2019-01-01 12:00:00")),
+                (String::from("2019-01-01 14:00:00"), String::from("2019-01-01 15:00:00"))
+            ]);
+        }
+
+        #[test]
+        fn merges_contained_windows() {
+            let mut windows = vec![
+                window("2019-01-01 10:00:00", "2019-01-01 18:00:00"),
+                window("2019-01-01 12:00:00", "2019-01-01 13:00:00"),
+                window("2019-01-01 15:00:00", "2019-01-01 15:00:00")
+            ];
+            DateTime::merge_windows(&mut windows);
+            assert_eq!(windows.iter().map(|w| (w.0.to_timestamp(None).unwrap(), w.1.to_timestamp(None).unwrap())).collect::<Vec<_>>(), vec![
+                (String::from("2019-01-01 10:00:00"), String::from("2019-01-01 18:00:00"))
+            ]);
+        }
+
+        #[test]
+        fn keeps_zero_length_windows() {
+            let mut windows = vec![
+                window("2019-01-01 10:00:00", "2019-01-01 10:00:00"),
+                window("2019-01-01 12:00:00", "2019-01-01 13:00:00")
+            ];
+            DateTime::merge_windows(&mut windows);
+            assert_eq!(windows.len(), 2);
+
+            windows = vec![];
+            DateTime::merge_windows(&mut windows);
+            assert!(windows.is_empty());
+        }
+    }
+
+    mod bytes {
+        use super::super::*;
+
+        #[test]
+        fn round_trips() {
+            let timeobj = DateTime::from_timestamp("2019-07-26 12:30:15", None).unwrap();
+            let parsed = DateTime::from_bytes(timeobj.to_bytes());
+            assert_eq!(parsed.to_timestamp(None).unwrap(), "2019-07-26 12:30:15");
+
+            let early = DateTime::from_epoch(-86_400);
+            assert_eq!(DateTime::from_bytes(early.to_bytes()).to_epoch(), -86_400);
+        }
+
+        #[test]
+        fn uses_big_endian_order() {
+            assert_eq!(DateTime::from_epoch(0x0102_0304).to_bytes(), [0, 0, 0, 0, 1, 2, 3, 4]);
+            assert_eq!(DateTime::from_bytes([0, 0, 0, 0, 0, 0, 1, 0]).to_epoch(), 256);
+        }
+    }
+
+    mod json_value {
+        use super::super::*;
+        use serde_json::{Map, json};
+
+        #[test]
+        fn writes_json_value() {
+            let timeobj = DateTime::from_timestamp("2019-01-01 12:00:00", Some("CET")).unwrap();
+            assert_eq!(timeobj.to_json_value(), json!("2019-01-01 11:00:00"));
+        }
+
+        #[test]
+        fn round_trips_through_json() {
+            let timeobj = DateTime::from_timestamp("2019-07-26 12:30:15", None).unwrap();
+            let mut map = Map::new();
+            map.insert(String::from("next_run"), timeobj.to_json_value());
+
+            let value = Value::Object(map);
+            let read = DateTime::from_json_value(&value["next_run"]).unwrap();
+            assert_eq!(read.to_epoch(), timeobj.to_epoch());
+        }
+
+        #[test]
+        fn throws_error_with_invalid_value() {
+            assert!(DateTime::from_json_value(&json!(12)).is_err());
+            assert!(DateTime::from_json_value(&json!("not a timestamp")).is_err());
+            assert!(DateTime::from_json_value(&Value::Null).is_err());
+        }
+    }
+
+    mod fire_tracker {
+        use super::super::*;
+
+        #[test]
+        fn fires_once_per_second() {
+            let mut tracker = FireTracker::new();
+            assert!(tracker.should_fire("00", &DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap()).unwrap());
+            assert!(!tracker.should_fire("00", &DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap()).unwrap());
+            assert!(!tracker.should_fire("00", &DateTime::from_timestamp("2019-01-01 12:00:30", None).unwrap()).unwrap());
+        }
+
+        #[test]
+        fn fires_in_every_cycle() {
+            let mut tracker = FireTracker::new();
+            assert!(!tracker.should_fire("00", &DateTime::from_timestamp("2019-01-01 11:59:59", None).unwrap()).unwrap());
+            assert!(tracker.should_fire("00", &DateTime::from_timestamp("2019-01-01 12:00:01", None).unwrap()).unwrap());
+            assert!(!tracker.should_fire("00", &DateTime::from_timestamp("2019-01-01 12:00:59", None).unwrap()).unwrap());
+            assert!(tracker.should_fire("00", &DateTime::from_timestamp("2019-01-01 12:01:00", None).unwrap()).unwrap());
+
+            // Missed cycles fire only once
+            assert!(tracker.should_fire("00", &DateTime::from_timestamp("2019-01-01 12:05:30", None).unwrap()).unwrap());
+            assert!(!tracker.should_fire("00", &DateTime::from_timestamp("2019-01-01 12:05:31", None).unwrap()).unwrap());
+        }
+
+        #[test]
+        fn tracks_patterns_separately() {
+            let mut tracker = FireTracker::new();
+            assert!(tracker.should_fire("00", &DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap()).unwrap());
+            assert!(tracker.should_fire("00:00", &DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap()).unwrap());
+            assert!(!tracker.should_fire("00:00", &DateTime::from_timestamp("2019-01-01 12:01:00", None).unwrap()).unwrap());
+        }
+
+        #[test]
+        fn fires_non_repeating_pattern_once() {
+            let mut tracker = FireTracker::new();
+            let pattern = "2019-01-01 12:00:00";
+            assert!(!tracker.should_fire(pattern, &DateTime::from_timestamp("2019-01-01 11:00:00", None).unwrap()).unwrap());
+            assert!(tracker.should_fire(pattern, &DateTime::from_timestamp("2019-01-01 12:00:05", None).unwrap()).unwrap());
+            assert!(!tracker.should_fire(pattern, &DateTime::from_timestamp("2019-01-01 13:00:00", None).unwrap()).unwrap());
+            assert!(FireTracker::new().should_fire(pattern, &DateTime::from_timestamp("2019-01-01 13:00:00", None).unwrap()).is_err());
+        }
+    }
+
+    mod _parse_natural {
+        use super::super::*;
+
+        #[test]
+        fn parses_relative_days() {
+            // A Friday
+            let ref_date = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            let mut timeobj = DateTime::_parse_natural("tomorrow 09:00:00", None, &ref_date).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-27 09:00:00");
+
+            timeobj = DateTime::_parse_natural("Today", None, &ref_date).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-26 00:00:00");
+
+            timeobj = DateTime::_parse_natural("yesterday 23:59:59", None, &ref_date).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-25 23:59:59");
+        }
+
+        #[test]
+        fn parses_next_weekday() {
+            // A Friday
+            let ref_date = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            let mut timeobj = DateTime::_parse_natural("next monday", None, &ref_date).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-29 00:00:00");
+
+            timeobj = DateTime::_parse_natural("next fri 10:00:00", None, &ref_date).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-08-02 10:00:00");
+        }
+
+        #[test]
+        fn uses_timezone() {
+            // Already Saturday in Tokyo
+            let ref_date = DateTime::from_timestamp("2019-07-26 20:00:00", None).unwrap();
+            let timeobj = DateTime::_parse_natural("today 09:00:00", Some("Asia/Tokyo"), &ref_date).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-27 00:00:00");
+        }
+
+        #[test]
+        fn throws_error_on_unrecognized_expression() {
+            // A Friday
+            let ref_date = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            assert!(DateTime::_parse_natural("the day after tomorrow", None, &ref_date).is_err());
+            assert!(DateTime::_parse_natural("next week", None, &ref_date).is_err());
+            assert!(DateTime::_parse_natural("tomorrow 9am", None, &ref_date).is_err());
+            assert!(DateTime::_parse_natural("", None, &ref_date).is_err());
+        }
+    }
+
+    mod _parse_relative {
+        use super::super::*;
+
+        #[test]
+        fn returns_reference_for_now() {
+            let ref_date = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            let timeobj = DateTime::_parse_relative("now", &ref_date).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-26 12:00:00");
+        }
+
+        #[test]
+        fn adds_offset() {
+            let ref_date = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            let timeobj = DateTime::_parse_relative("now+1:30:0", &ref_date).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-26 13:30:00");
+        }
+
+        #[test]
+        fn subtracts_offset() {
+            let ref_date = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            let timeobj = DateTime::_parse_relative("now-1 0:0:0", &ref_date).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-25 12:00:00");
+        }
+
+        #[test]
+        fn throws_error_with_invalid_expression() {
+            let ref_date = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            assert!(DateTime::_parse_relative("later", &ref_date).is_err());
+            assert!(DateTime::_parse_relative("now*2", &ref_date).is_err());
+            assert!(DateTime::_parse_relative("now+soon", &ref_date).is_err());
         }
 
         #[test]
-        fn returns_true_if_passed() {
-            let timeobj = DateTime::from_timestamp("1972-02-02 22:22:22", None).unwrap();
-            assert!(timeobj.is_passed(None));
+        fn uses_current_time_as_reference() {
+            let before = DateTime::now().to_epoch();
+            let timeobj = DateTime::parse_relative("now").unwrap();
+            assert!(timeobj.to_epoch() >= before && timeobj.to_epoch() - before <= 1);
         }
     }
 
-    mod _merge_error {
+    mod _relative_to {
         use super::super::*;
 
         #[test]
-        fn returns_result_if_not_null() {
-            let chrono_res = Utc::now().with_minute(40);
-            let merge_res = DateTime::_merge_error(chrono_res, String::from("Some error message."));
-            assert!(merge_res.is_ok())
+        fn describes_past() {
+            let now = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            assert_eq!(DateTime::from_timestamp("2019-01-01 09:00:00", None).unwrap()._relative_to(&now), "3 hours ago");
+            assert_eq!(DateTime::from_timestamp("2019-01-01 11:59:00", None).unwrap()._relative_to(&now), "1 minute ago");
+            assert_eq!(DateTime::from_timestamp("2018-12-29 11:00:00", None).unwrap()._relative_to(&now), "3 days ago");
         }
 
         #[test]
-        fn returns_error_if_null() {
-            let chrono_res = Utc::now().with_minute(70);
-            let merge_res = DateTime::_merge_error(chrono_res, String::from("Some error message."));
-            assert!(merge_res.is_err());
+        fn describes_future() {
+            let now = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            assert_eq!(DateTime::from_timestamp("2019-01-01 12:05:30", None).unwrap()._relative_to(&now), "in 5 minutes");
+            assert_eq!(DateTime::from_timestamp("2019-01-01 12:00:10", None).unwrap()._relative_to(&now), "in 10 seconds");
+            assert_eq!(DateTime::from_timestamp("2019-01-02 12:00:00", None).unwrap()._relative_to(&now), "in 1 day");
+        }
 
-            let err = merge_res.err().unwrap();
-            assert_eq!(*err.message, String::from("Some error message."));
+        #[test]
+        fn describes_near_now() {
+            let now = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            assert_eq!(DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap()._relative_to(&now), "just now");
+            assert_eq!(DateTime::from_timestamp("2019-01-01 12:00:01", None).unwrap()._relative_to(&now), "just now");
+            assert_eq!(DateTime::from_timestamp("2019-01-01 11:59:59", None).unwrap()._relative_to(&now), "just now");
+            assert_eq!(DateTime::from_timestamp("2019-01-01 12:00:02", None).unwrap()._relative_to(&now), "in 2 seconds");
         }
     }
 
-    mod _merge_timefreq {
+    mod count_occurrences {
         use super::super::*;
 
         #[test]
-        fn merges_seconds() {
-            let old_now = Utc::now();
-            let mut dt = DateTime::now();
-            let mut tf = TimeFreq::from_timestamp("10", true).unwrap();
-            dt._merge_timefreq(&mut tf).unwrap();
-
-            assert_eq!(dt.dt.second(), 10);
-            assert_eq!(dt.dt.minute(), old_now.minute());
-            assert_eq!(dt.dt.hour(), old_now.hour());
-            assert_eq!(dt.dt.day(), old_now.day());
-            assert_eq!(dt.dt.month(), old_now.month());
-            assert_eq!(dt.dt.year(), old_now.year());
+        fn counts_fixed_cycles() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-01 23:59:59", None).unwrap();
+            assert_eq!(DateTime::count_occurrences("00:00", &start, &end).unwrap(), 24);
+            assert_eq!(DateTime::count_occurrences("30:00", &start, &end).unwrap(), 24);
+            assert_eq!(DateTime::count_occurrences("12:00:00", &start, &end).unwrap(), 1);
+            assert_eq!(DateTime::count_occurrences("30", &start, &end).unwrap(), 1440);
         }
 
         #[test]
-        fn merges_minutes() {
-            let old_now = Utc::now();
-            let mut dt = DateTime::now();
-            let mut tf = TimeFreq::from_timestamp("20:0", true).unwrap();
-            dt._merge_timefreq(&mut tf).unwrap();
-
-            assert_eq!(dt.dt.second(), 0);
-            assert_eq!(dt.dt.minute(), 20);
-            assert_eq!(dt.dt.hour(), old_now.hour());
-            assert_eq!(dt.dt.day(), old_now.day());
-            assert_eq!(dt.dt.month(), old_now.month());
-            assert_eq!(dt.dt.year(), old_now.year());
+        fn counts_calendar_cycles() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-12-31 23:59:59", None).unwrap();
+            assert_eq!(DateTime::count_occurrences("1 00:00:00", &start, &end).unwrap(), 12);
+            assert_eq!(DateTime::count_occurrences("07-26 12:00:00", &start, &end).unwrap(), 1);
         }
 
         #[test]
-        fn merges_hours() {
-            let old_now = Utc::now();
-            let mut dt = DateTime::now();
-            let mut tf = TimeFreq::from_timestamp("12:0:0", true).unwrap();
-            dt._merge_timefreq(&mut tf).unwrap();
-
-            assert_eq!(dt.dt.second(), 0);
-            assert_eq!(dt.dt.minute(), 0);
-            assert_eq!(dt.dt.hour(), 12);
-            assert_eq!(dt.dt.day(), old_now.day());
-            assert_eq!(dt.dt.month(), old_now.month());
-            assert_eq!(dt.dt.year(), old_now.year());
+        fn returns_zero_for_empty_window() {
+            let start = DateTime::from_timestamp("2019-01-01 10:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-01-01 11:00:00", None).unwrap();
+            assert_eq!(DateTime::count_occurrences("12:00:00", &start, &end).unwrap(), 0);
+            assert_eq!(DateTime::count_occurrences("00:00", &end, &start).unwrap(), 0);
         }
 
         #[test]
-        fn merges_days() {
-            let old_now = Utc::now();
-            let mut dt = DateTime::now();
-            let mut tf = TimeFreq::from_timestamp("28 0:0:0", true).unwrap();
-            dt._merge_timefreq(&mut tf).unwrap();
-
-            assert_eq!(dt.dt.second(), 0);
-            assert_eq!(dt.dt.minute(), 0);
-            assert_eq!(dt.dt.hour(), 0);
-            assert_eq!(dt.dt.day(), 28);
-            assert_eq!(dt.dt.month(), old_now.month());
-            assert_eq!(dt.dt.year(), old_now.year());
+        fn throws_error_for_non_repeating_pattern() {
+            let start = DateTime::from_timestamp("2019-01-01 00:00:00", None).unwrap();
+            let end = DateTime::from_timestamp("2019-12-31 23:59:59", None).unwrap();
+            assert!(DateTime::count_occurrences("2019-07-26 12:00:00", &start, &end).is_err());
         }
+    }
 
-        #[test]
-        fn merges_months() {
-            let old_now = Utc::now();
-            let mut dt = DateTime::now();
-            let mut tf = TimeFreq::from_timestamp("9-1 0:0:0", true).unwrap();
-            dt._merge_timefreq(&mut tf).unwrap();
+    mod step_iter {
+        use super::super::*;
 
-            assert_eq!(dt.dt.second(), 0);
-            assert_eq!(dt.dt.minute(), 0);
-            assert_eq!(dt.dt.hour(), 0);
-            assert_eq!(dt.dt.day(), 1);
-            assert_eq!(dt.dt.month(), 9);
-            assert_eq!(dt.dt.year(), old_now.year());
+        #[test]
+        fn steps_by_fixed_duration() {
+            let start = DateTime::from_timestamp("2019-01-01 23:30:00", None).unwrap();
+            let steps: Vec<String> = DateTime::step_iter(start, "15:00").unwrap().take(4)
+                .map(|dt| dt.to_timestamp(None).unwrap()).collect();
+            assert_eq!(steps, vec!["2019-01-01 23:30:00", "2019-01-01 23:45:00",
+                "2019-01-02 00:00:00", "2019-01-02 00:15:00"]);
         }
 
         #[test]
-        fn merges_years() {
-            let mut dt = DateTime::now();
-            let mut tf = TimeFreq::from_timestamp("1001-1-1 0:0:0", true).unwrap();
-            dt._merge_timefreq(&mut tf).unwrap();
-
-            assert_eq!(dt.dt.second(), 0);
-            assert_eq!(dt.dt.minute(), 0);
-            assert_eq!(dt.dt.hour(), 0);
-            assert_eq!(dt.dt.day(), 1);
-            assert_eq!(dt.dt.month(), 1);
-            assert_eq!(dt.dt.year(), 1001);
+        fn steps_by_months() {
+            let start = DateTime::from_timestamp("2019-11-15 00:00:00", None).unwrap();
+            let steps: Vec<String> = DateTime::step_iter(start, "0-1-0 0:0:0").unwrap().take(3)
+                .map(|dt| dt.to_timestamp(None).unwrap()).collect();
+            assert_eq!(steps, vec!["2019-11-15 00:00:00", "2019-12-15 00:00:00", "2020-01-15 00:00:00"]);
         }
 
         #[test]
-        fn returns_error_if_cannot_be_merged() {
-            let mut dt = DateTime::now();
-            let mut tf = TimeFreq::from_timestamp("25:70:90", true).unwrap();
-            let mut res = dt._merge_timefreq(&mut tf);
-            assert!(res.is_err());
-
-            //Day cannot be 0, only if it is not part of the relative date
-            tf = TimeFreq::from_timestamp("0-1-0 0:0:0", true).unwrap();
-            res = dt._merge_timefreq(&mut tf);
-            assert!(res.is_err());
-
-            //Month cannot be 0, only if it is not part of the relative date
-            tf = TimeFreq::from_timestamp("1001-0-1 0:0:0", true).unwrap();
-            res = dt._merge_timefreq(&mut tf);
-            assert!(res.is_err());
+        fn throws_error_with_zero_step() {
+            assert!(DateTime::step_iter(DateTime::now(), "0:00:00").is_err());
+            assert!(DateTime::step_iter(DateTime::now(), "0-0-0 0:0:0").is_err());
+            assert!(DateTime::step_iter(DateTime::now(), "soon").is_err());
         }
     }
 
-    mod next_occurrence {
+    mod drift {
         use super::super::*;
 
         #[test]
-        fn uses_current_time_as_reference() {
-            // Will fail at every exact hour, but currently IDC
-            let mut now = Utc::now();
-            let min = now.minute() - 1;
-            let mut dt = DateTime::next_occurrence(&format!("{}:00", min)).unwrap();
-            let mut expected = format!("{}-{:0width$}-{:0width$} {:0width$}:{:0width$}:{}",
-                now.year(), now.month(), now.day(), now.hour() + 1, min, "00", width = 2);
+        fn is_zero_on_time() {
+            let expected = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            let actual = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            assert_eq!(DateTime::drift(&expected, &actual), Duration::zero());
+            assert!(DateTime::within_tolerance(&expected, &actual, Duration::zero()));
+        }
 
-            assert_eq!(dt.to_timestamp(None).unwrap(), expected);
+        #[test]
+        fn is_negative_if_early() {
+            let expected = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            let actual = DateTime::from_timestamp("2019-01-01 11:59:30", None).unwrap();
+            assert_eq!(DateTime::drift(&expected, &actual), Duration::seconds(-30));
+            assert!(DateTime::within_tolerance(&expected, &actual, Duration::seconds(30)));
+            assert!(!DateTime::within_tolerance(&expected, &actual, Duration::seconds(29)));
+        }
 
-            // TODO: test if it fails between midnight and 1am
-            now = Utc::now();
-            let hr;
-            let mut exp_dt;
-            if now.hour() == 0 {
-                exp_dt = DateTime::now();
-                hr = 1;
-            } else {
-                exp_dt = DateTime::now();
-                exp_dt.add("1 00:00:00").unwrap();
-                hr = now.hour() - 1;
-            }
-            dt = DateTime::next_occurrence(&format!("{}:00:00", hr)).unwrap();
-            expected = format!("{}-{:0width$}-{:0width$} {:0width$}:{:0width$}:{}",
-                exp_dt.dt.year(), exp_dt.dt.month(), exp_dt.dt.day(), hr, "00", "00", width = 2);
-            assert_eq!(dt.to_timestamp(None).unwrap(), expected);
+        #[test]
+        fn is_positive_if_late() {
+            let expected = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
+            let actual = DateTime::from_timestamp("2019-01-01 12:02:00", None).unwrap();
+            assert_eq!(DateTime::drift(&expected, &actual), Duration::minutes(2));
+            assert!(DateTime::within_tolerance(&expected, &actual, Duration::minutes(-2)));
+            assert!(!DateTime::within_tolerance(&expected, &actual, Duration::minutes(1)));
         }
     }
 
-    mod _next_occurrence {
+    mod total_coverage {
         use super::super::*;
+        use super::window;
 
         #[test]
-        fn correct_in_common_cases() {
-            let mut dt = DateTime::from_timestamp("2019-01-01 10:00:05", None).unwrap();
-            let mut next_occur = DateTime::_next_occurrence("04", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-01-01 10:01:04");
+        fn sums_disjoint_windows() {
+            let windows = vec![
+                window("2019-01-01 10:00:00", "2019-01-01 12:00:00"),
+                window("2019-01-01 14:00:00", "2019-01-01 14:30:00")
+            ];
+            assert_eq!(DateTime::total_coverage(&windows), Duration::minutes(150));
+        }
 
-            dt = DateTime::from_timestamp("2019-01-01 10:05:00", None).unwrap();
-            next_occur = DateTime::_next_occurrence("04:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-01-01 11:04:00");
+        #[test]
+        fn does_not_double_count_overlaps() {
+            let windows = vec![
+                window("2019-01-01 10:00:00", "2019-01-01 12:00:00"),
+                window("2019-01-01 11:00:00", "2019-01-01 13:00:00"),
+                window("2019-01-01 11:30:00", "2019-01-01 11:45:00")
+            ];
+            assert_eq!(DateTime::total_coverage(&windows), Duration::hours(3));
+        }
 
-            dt = DateTime::from_timestamp("2019-01-01 10:00:00", None).unwrap();
-            next_occur = DateTime::_next_occurrence("09:02:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-01-02 09:02:00");
+        #[test]
+        fn ignores_negative_windows() {
+            let windows = vec![
+                window("2019-01-01 10:00:00", "2019-01-01 11:00:00"),
+                window("2019-01-01 18:00:00", "2019-01-01 08:00:00")
+            ];
+            assert_eq!(DateTime::total_coverage(&windows), Duration::hours(1));
+            assert_eq!(DateTime::total_coverage(&[]), Duration::zero());
+        }
+    }
 
-            dt = DateTime::from_timestamp("2019-01-01 11:00:00", None).unwrap();
-            next_occur = DateTime::_next_occurrence("10:02:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-01-02 10:02:00");
+    mod common_free_window {
+        use super::super::*;
+        use super::window;
 
-            dt = DateTime::from_timestamp("2019-01-01 10:00:00", None).unwrap();
-            next_occur = DateTime::_next_occurrence("00:00:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-01-02 00:00:00");
+        #[test]
+        fn finds_overlapping_free_slot() {
+            let busy = vec![
+                vec![window("2019-01-01 08:00:00", "2019-01-01 10:00:00"), window("2019-01-01 13:00:00", "2019-01-01 18:00:00")],
+                vec![window("2019-01-01 09:00:00", "2019-01-01 11:00:00"), window("2019-01-01 11:30:00", "2019-01-01 12:00:00")]
+            ];
+            let (start, end) = window("2019-01-01 08:00:00", "2019-01-01 18:00:00");
+
+            let (free_start, free_end) = DateTime::common_free_window(&busy, &start, &end, Duration::minutes(30)).unwrap();
+            assert_eq!(free_start.to_timestamp(None).unwrap(), "2019-01-01 11:00:00");
+            assert_eq!(free_end.to_timestamp(None).unwrap(), "2019-01-01 11:30:00");
+
+            let (free_start, free_end) = DateTime::common_free_window(&busy, &start, &end, Duration::minutes(45)).unwrap();
+            assert_eq!(free_start.to_timestamp(None).unwrap(), "2019-01-01 12:00:00");
+            assert_eq!(free_end.to_timestamp(None).unwrap(), "2019-01-01 13:00:00");
+        }
 
-            dt = DateTime::from_timestamp("2019-01-12 10:00:00", None).unwrap();
-            next_occur = DateTime::_next_occurrence("07 00:00:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-02-07 00:00:00");
+        #[test]
+        fn uses_range_ends() {
+            let busy = vec![vec![window("2019-01-01 10:00:00", "2019-01-01 12:00:00")]];
+            let (start, end) = window("2019-01-01 08:00:00", "2019-01-01 16:00:00");
 
-            dt = DateTime::from_timestamp("2019-03-12 10:00:00", None).unwrap();
-            next_occur = DateTime::_next_occurrence("02-01 00:00:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2020-02-01 00:00:00");
+            let (free_start, _) = DateTime::common_free_window(&busy, &start, &end, Duration::hours(2)).unwrap();
+            assert_eq!(free_start.to_timestamp(None).unwrap(), "2019-01-01 08:00:00");
 
-            // Neccessary for defining one-shots in an unusual way
-            dt = DateTime::from_timestamp("2019-03-12 10:00:00", None).unwrap();
-            next_occur = DateTime::_next_occurrence("2020-01-01 00:00:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2020-01-01 00:00:00");
+            let (free_start, free_end) = DateTime::common_free_window(&busy, &start, &end, Duration::hours(3)).unwrap();
+            assert_eq!(free_start.to_timestamp(None).unwrap(), "2019-01-01 12:00:00");
+            assert_eq!(free_end.to_timestamp(None).unwrap(), "2019-01-01 16:00:00");
         }
 
         #[test]
-        fn handles_edge_cases() {
-            let mut dt = DateTime::from_timestamp("2019-01-31 10:00:00", None).unwrap();
-            let mut next_occur = DateTime::_next_occurrence("31 00:00:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-03-03 00:00:00");
+        fn returns_none_without_common_window() {
+            let busy = vec![
+                vec![window("2019-01-01 08:00:00", "2019-01-01 12:00:00")],
+                vec![window("2019-01-01 11:00:00", "2019-01-01 18:00:00")]
+            ];
+            let (start, end) = window("2019-01-01 08:00:00", "2019-01-01 18:00:00");
+            assert!(DateTime::common_free_window(&busy, &start, &end, Duration::minutes(1)).is_none());
+        }
+    }
 
-            dt = DateTime::from_timestamp("2019-07-31 12:00:00", None).unwrap();
-            next_occur = DateTime::_next_occurrence("31 11:30:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-08-31 11:30:00");
+    mod from_timestamp_with_resolution {
+        use super::super::*;
 
-            dt = DateTime::from_timestamp("2019-02-15 12:00:00", None).unwrap();
-            next_occur = DateTime::_next_occurrence("30 11:30:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-03-02 11:30:00");
+        #[test]
+        fn reads_full_timestamp() {
+            let (timeobj, res) = DateTime::from_timestamp_with_resolution("2019-01-01 12:30:15", None).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-01-01 12:30:15");
+            assert!(res == Resolution::Second);
+        }
 
-            dt = DateTime::from_timestamp("2019-01-01 12:00:00", None).unwrap();
-            next_occur = DateTime::_next_occurrence("02-29 11:30:00", &dt).unwrap();
-            assert_eq!(next_occur.to_timestamp(None).unwrap(), "2019-03-01 11:30:00");
+        #[test]
+        fn reads_truncated_timestamps() {
+            let (mut timeobj, mut res) = DateTime::from_timestamp_with_resolution("2019-07-26", None).unwrap();
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-26 00:00:00");
+            assert!(res == Resolution::Day);
+
+            let parsed = DateTime::from_timestamp_with_resolution("2019-07-26 12:30", Some("CET")).unwrap();
+            timeobj = parsed.0;
+            res = parsed.1;
+            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2019-07-26 10:30:00");
+            assert!(res == Resolution::Minute);
+
+            let parsed = DateTime::from_timestamp_with_resolution("2019-07", None).unwrap();
+            assert_eq!(parsed.0.to_timestamp(None).unwrap(), "2019-07-01 00:00:00");
+            assert!(parsed.1 == Resolution::Month);
+
+            let parsed = DateTime::from_timestamp_with_resolution("2019", None).unwrap();
+            assert!(parsed.1 == Resolution::Year);
+        }
+
+        #[test]
+        fn throws_error_with_invalid_timestamp() {
+            assert!(DateTime::from_timestamp_with_resolution("", None).is_err());
+            assert!(DateTime::from_timestamp_with_resolution("2019-07 12:00", None).is_err());
+            assert!(DateTime::from_timestamp_with_resolution("2019-02-30", None).is_err());
+            assert!(DateTime::from_timestamp_with_resolution("2019-01-01 12:00:00 CET", None).is_err());
         }
     }
 
-    mod _add_months {
+    mod next_business_time {
         use super::super::*;
 
         #[test]
-        fn handles_normal_cases() {
-            let mut timeobj = DateTime::from_timestamp("2018-08-15 10:30:00", None).unwrap();
-            timeobj._add_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-09-15 10:30:00");
-
-            timeobj._add_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-10-15 10:30:00");
-
-            timeobj._add_months(2);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-12-15 10:30:00");
+        fn returns_self_inside_hours() {
+            let timeobj = DateTime::from_timestamp("2019-07-26 12:30:15", None).unwrap();
+            let next = timeobj.next_business_time("09:00:00", "17:00:00").unwrap();
+            assert_eq!(next.to_timestamp(None).unwrap(), "2019-07-26 12:30:15");
         }
 
         #[test]
-        fn handles_edge_cases() {
-            let mut timeobj = DateTime::from_timestamp("2018-03-31 10:30:00", None).unwrap();
-            timeobj._add_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-05-01 10:30:00");
+        fn returns_opening_before_hours() {
+            let timeobj = DateTime::from_timestamp("2019-07-25 06:00:00", None).unwrap();
+            let next = timeobj.next_business_time("09:00:00", "17:00:00").unwrap();
+            assert_eq!(next.to_timestamp(None).unwrap(), "2019-07-25 09:00:00");
+        }
 
-            timeobj = DateTime::from_timestamp("2018-01-31 10:30:00", None).unwrap();
-            timeobj._add_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-03-03 10:30:00");
+        #[test]
+        fn returns_next_opening_after_close() {
+            let mut timeobj = DateTime::from_timestamp("2019-07-25 17:00:00", None).unwrap();
+            let mut next = timeobj.next_business_time("09:00:00", "17:00:00").unwrap();
+            assert_eq!(next.to_timestamp(None).unwrap(), "2019-07-26 09:00:00");
+
+            // Friday evening
+            timeobj = DateTime::from_timestamp("2019-07-26 18:00:00", None).unwrap();
+            next = timeobj.next_business_time("09:00:00", "17:00:00").unwrap();
+            assert_eq!(next.to_timestamp(None).unwrap(), "2019-07-29 09:00:00");
+        }
 
-            timeobj = DateTime::from_timestamp("2018-01-30 10:30:00", None).unwrap();
-            timeobj._add_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-03-02 10:30:00");
+        #[test]
+        fn jumps_over_weekend() {
+            let mut timeobj = DateTime::from_timestamp("2019-07-27 12:00:00", None).unwrap();
+            let mut next = timeobj.next_business_time("09:00:00", "17:00:00").unwrap();
+            assert_eq!(next.to_timestamp(None).unwrap(), "2019-07-29 09:00:00");
+
+            timeobj = DateTime::from_timestamp("2019-07-28 23:59:59", None).unwrap();
+            next = timeobj.next_business_time("09:00:00", "17:00:00").unwrap();
+            assert_eq!(next.to_timestamp(None).unwrap(), "2019-07-29 09:00:00");
+        }
 
-            timeobj = DateTime::from_timestamp("2018-01-29 10:30:00", None).unwrap();
-            timeobj._add_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-03-01 10:30:00");
+        #[test]
+        fn throws_error_with_invalid_window() {
+            let timeobj = DateTime::from_timestamp("2019-07-26 12:00:00", None).unwrap();
+            assert!(timeobj.next_business_time("17:00:00", "09:00:00").is_err());
+            assert!(timeobj.next_business_time("1 09:00:00", "17:00:00").is_err());
+            assert!(timeobj.next_business_time("09:00:00", "24:00:00").is_err());
         }
     }
 
-    mod _sub_months {
+    mod describe_pattern {
         use super::super::*;
 
         #[test]
-        fn handles_normal_cases() {
-            let mut timeobj = DateTime::from_timestamp("2018-12-15 10:30:00", None).unwrap();
-            timeobj._sub_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-11-15 10:30:00");
-
-            timeobj._sub_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-10-15 10:30:00");
-
-            timeobj._sub_months(2);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-08-15 10:30:00");
+        fn describes_daily_pattern() {
+            assert_eq!(DateTime::describe_pattern("9:30:0").unwrap(), "Every day at 09:30:00");
         }
 
         #[test]
-        fn handles_edge_cases() {
-            let mut timeobj = DateTime::from_timestamp("2018-05-31 10:30:00", None).unwrap();
-            timeobj._sub_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-04-30 10:30:00");
-
-            timeobj = DateTime::from_timestamp("2018-03-31 10:30:00", None).unwrap();
-            timeobj._sub_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-02-28 10:30:00");
+        fn describes_hourly_and_minutely_patterns() {
+            assert_eq!(DateTime::describe_pattern("15:00").unwrap(), "Every hour at 15:00");
+            assert_eq!(DateTime::describe_pattern("5").unwrap(), "Every minute at second 05");
+        }
 
-            timeobj = DateTime::from_timestamp("2018-03-30 10:30:00", None).unwrap();
-            timeobj._sub_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-02-27 10:30:00");
+        #[test]
+        fn describes_calendar_patterns() {
+            assert_eq!(DateTime::describe_pattern("1 00:00:00").unwrap(), "Every month on day 1 at 00:00:00");
+            assert_eq!(DateTime::describe_pattern("07-26 12:00:00").unwrap(), "Every year on 07-26 at 12:00:00");
+            assert_eq!(DateTime::describe_pattern("2019-07-26 12:00:00").unwrap(), "Once on 2019-07-26 at 12:00:00");
+        }
 
-            timeobj = DateTime::from_timestamp("2018-03-29 10:30:00", None).unwrap();
-            timeobj._sub_months(1);
-            assert_eq!(timeobj.to_timestamp(None).unwrap(), "2018-02-26 10:30:00");
+        #[test]
+        fn throws_error_with_invalid_pattern() {
+            assert!(DateTime::describe_pattern("every day").is_err());
+            assert!(DateTime::describe_pattern("25:00:00").is_err());
+            assert!(DateTime::describe_pattern("0 12:00:00").is_err());
+            assert!(DateTime::describe_pattern("13-01 12:00:00").is_err());
         }
     }
 }
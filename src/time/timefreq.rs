@@ -1,13 +1,18 @@
 use std::error::Error;
+use std::ops::Add;
 use super::super::error::PafError;
 
+/// The longest accepted partial timestamp. Valid timestamps are much shorter, longer inputs
+/// are rejected before parsing.
+const MAX_TIMESTAMP_LEN: usize = 64;
+
 enum DateOrTime {
     Date,
     Time
 }
 
 /// Enum for the largest user defined member.
-#[derive(PartialEq, PartialOrd)]
+#[derive(PartialEq, PartialOrd, Clone, Copy, Debug)]
 pub enum Resolution {
     Year = 6,
     Month = 5,
@@ -18,6 +23,61 @@ pub enum Resolution {
     None = 0
 }
 
+impl Resolution {
+    /// Returns every resolution ordered from the coarsest to the finest.
+    /// 
+    /// ## Examples
+    /// ```
+    /// assert!(Resolution::all()[0] == Resolution::Year);
+    /// ```
+    pub fn all() -> [Resolution; 7] {
+        [Resolution::Year, Resolution::Month, Resolution::Day, Resolution::Hour,
+            Resolution::Minute, Resolution::Second, Resolution::None]
+    }
+
+    /// Checks if the resolution is strictly finer than another one.
+    /// 
+    /// ## Arguments
+    /// * `other` - The resolution to compare to
+    /// 
+    /// ## Examples
+    /// ```
+    /// assert!(Resolution::Second.finer_than(&Resolution::Hour));
+    /// ```
+    pub fn finer_than(&self, other: &Resolution) -> bool {
+        self < other
+    }
+
+    /// Checks if the resolution is strictly coarser than another one.
+    /// 
+    /// ## Arguments
+    /// * `other` - The resolution to compare to
+    /// 
+    /// ## Examples
+    /// ```
+    /// assert!(Resolution::Day.coarser_than(&Resolution::Hour));
+    /// ```
+    pub fn coarser_than(&self, other: &Resolution) -> bool {
+        self > other
+    }
+}
+
+/// Enum for handling components exceeding their natural range (e.g. 90 seconds).
+/// 
+/// * `Keep` - components are left as they are, which is meaningful for raw durations
+/// * `Carry` - excess is carried into the next larger component (e.g. 90 seconds becomes
+///   1 minute and 30 seconds), which is meaningful for display. Days are never carried into
+///   months, as months do not have a fixed length.
+/// 
+/// `TimeFreq::normalize` and `TimeFreq::add_with_policy` take an explicit policy, while
+/// the `+` operator uses the default `Carry` policy.
+#[derive(Default)]
+pub enum OverflowPolicy {
+    Keep,
+    #[default]
+    Carry
+}
+
 /// Struct for parsing and storing partial times and frequencies
 /// (e.g. 15:00 translates to every 15 minutes in frequency and 00:15:00 in time)
 /// used for scheduling.
@@ -52,6 +112,7 @@ pub enum Resolution {
 /// 0:0:5|00-00-00 00:00:05|Next day with 00:00:05
 /// * DateTimes - similarly, undefined values should be omitted from left to right,
 /// i.e. years to seconds. Same patterns apply as in dates and times.
+#[derive(Clone)]
 pub struct TimeFreq {
     // Time/frequency components
     // NOTE: chrono::DateTime uses i32 for years, as it needs to handle BC times. We neglect them as
@@ -93,6 +154,23 @@ impl TimeFreq {
         str_arr.retain(|e| !e.trim().is_empty());
     }
 
+    /// Utility function for removing whitespaces around the separators of a partial time
+    /// string (e.g. `" 1 : 0 : 0 "` becomes `"1:0:0"`). Other whitespaces are collapsed
+    /// into single spaces.
+    /// 
+    /// ## Arguments
+    /// * `timestamp` - The partial timestamp
+    fn _join_separators(timestamp: &str) -> String {
+        let is_sep = |c: char| c == ':' || c == '-';
+        timestamp.split_whitespace().fold(String::new(), |mut joined, part| {
+            if !joined.is_empty() && !joined.ends_with(is_sep) && !part.starts_with(is_sep) {
+                joined.push(' ');
+            }
+            joined.push_str(part);
+            joined
+        })
+    }
+
     /// Utility function for splitting a partial time or date and parsing the components
     /// as integers. If it can parse every component, it returns an array with 4 elements.
     /// The 4th element is the resolution, since the user can provide a time or a date
@@ -118,14 +196,19 @@ impl TimeFreq {
             return Err(PafError::create_error("Invalid timestamp."));
         }
 
-        // Try to parse elements
-        for i in 0..timestamp_str_arr.len() {
-            let val: u32 = timestamp_str_arr[i].trim().parse()?;
+        // Try to parse elements. Only ASCII digits are accepted, as `parse` would allow signs.
+        for elem in timestamp_str_arr.iter().map(|e| e.trim()) {
+            if !elem.chars().all(|c| c.is_ascii_digit()) {
+                return Err(PafError::create_error(&format!("Invalid timestamp component {}.", elem)));
+            }
+            let val: u32 = elem.parse()
+                .map_err(|_| PafError::create_error(&format!("Too large timestamp component {}.", elem)))?;
             timestamp_arr.push(val);
         }
 
         // Provide resolution as a 4th array member
-        let res = 3 - timestamp_arr.len() as u32;
+        let res = 3u32.checked_sub(timestamp_arr.len() as u32)
+            .ok_or_else(|| PafError::create_error("Invalid timestamp."))?;
 
         for _ in 0..res {
             timestamp_arr.insert(0, 0);
@@ -188,8 +271,13 @@ impl TimeFreq {
         let mut date_arr: Vec<u32> = vec![0, 0, 0, 3];
         let mut time_arr: Vec<u32> = vec![0, 0, 0, 3];
 
+        if timestamp.trim().len() > MAX_TIMESTAMP_LEN {
+            return Err(PafError::create_error("Failed to parse too long timestamp."));
+        }
+
         // Process input string
-        let mut ts_arr: Vec<&str> = timestamp.trim().split(" ").collect();
+        let joined = TimeFreq::_join_separators(timestamp);
+        let mut ts_arr: Vec<&str> = joined.split(" ").collect();
 
         // Try to correct bad formatting
         TimeFreq::sanitize_timestr_arr(&mut ts_arr);
@@ -224,11 +312,9 @@ impl TimeFreq {
 
         // Convert excess months to years
         if wrap_years && date_arr[1] >= 12 {
-            let years_f: f32 = date_arr[1] as f32 / 12.0;
-            let years = years_f.floor() as u32;
-
-            date_arr[0] += years;
-            date_arr[1] -= years * 12;
+            date_arr[0] = date_arr[0].checked_add(date_arr[1] / 12)
+                .ok_or_else(|| PafError::create_error("Too many years in timestamp."))?;
+            date_arr[1] %= 12;
         }
 
         Ok(TimeFreq {
@@ -243,6 +329,317 @@ impl TimeFreq {
         })
     }
 
+    /// Creates a `TimeFreq` object from a partial timestamp used as a frequency. Works like
+    /// `from_timestamp`, but also raises an error, if every component is zero, as it would
+    /// mean repeating in every zero seconds.
+    /// 
+    /// ## Arguments
+    /// * `timestamp` - the partial timestamp
+    /// * `wrap_years` - a bool telling the method if it should convert months > 12 to years
+    /// 
+    /// ## Examples
+    /// ```
+    /// assert!(TimeFreq::from_timestamp_strict("0:15:0", true).is_ok());
+    /// assert!(TimeFreq::from_timestamp_strict("0:0:0", true).is_err());
+    /// ```
+    pub fn from_timestamp_strict(timestamp: &str, wrap_years: bool) -> Result<TimeFreq, Box<Error>> {
+        let tf = TimeFreq::from_timestamp(timestamp, wrap_years)?;
+        if tf.is_zero() {
+            return Err(PafError::create_error(&format!("Invalid zero frequency {:?}.", timestamp)));
+        }
+        Ok(tf)
+    }
+
+    /// Parses a list of partial timestamps separated by semicolons or commas (e.g.
+    /// `"08:00:00;12:00:00"`). Patterns are trimmed, and parsed with `from_timestamp` without
+    /// wrapping years. Raises an error with the zero-based index of the first invalid pattern,
+    /// including empty ones.
+    /// 
+    /// ## Arguments
+    /// * `s` - the list of partial timestamps
+    /// 
+    /// ## Examples
+    /// ```
+    /// let patterns = TimeFreq::parse_patterns("08:00:00; 1-15 12:00:00").unwrap();
+    /// assert_eq!(patterns.len(), 2);
+    /// ```
+    pub fn parse_patterns(s: &str) -> Result<Vec<TimeFreq>, Box<Error>> {
+        s.split(&[';', ','][..]).enumerate().map(|(i, pattern)| {
+            TimeFreq::from_timestamp(pattern.trim(), false)
+                .map_err(|e| PafError::create_error(&format!("Invalid pattern {:?} at index {}: {}", pattern.trim(), i, e)).into())
+        }).collect()
+    }
+
+    /// Creates a `TimeFreq` object from a unit-suffixed shorthand duration (e.g. `"1h30m"`).
+    /// Tokens can be provided in any order, and repeated units are summed. If it cannot parse
+    /// the shorthand, it raises an error.
+    /// 
+    /// Supported units are `y` (years), `mo` (months), `d` (days), `h` (hours), `m` (minutes),
+    /// and `s` (seconds). The resolution is set to the largest provided unit.
+    /// 
+    /// ## Arguments
+    /// * `s` - the shorthand duration
+    /// 
+    /// ## Examples
+    /// ```
+    /// // 1 hour and 30 mins
+    /// let tf = TimeFreq::from_shorthand("1h30m").unwrap();
+    /// 
+    /// // 1 month and 15 days
+    /// let tf = TimeFreq::from_shorthand("1mo15d").unwrap();
+    /// 
+    /// // parse error
+    /// let tf = TimeFreq::from_shorthand("5x").unwrap();
+    /// ```
+    pub fn from_shorthand(s: &str) -> Result<TimeFreq, Box<Error>> {
+        let mut tf: TimeFreq = Default::default();
+        let mut chars = s.trim().chars().filter(|c| !c.is_whitespace()).peekable();
+
+        if chars.peek().is_none() {
+            return Err(PafError::create_error("Failed to parse empty duration."));
+        }
+
+        while chars.peek().is_some() {
+            // Every token is a number followed by a unit
+            let mut num_str = String::new();
+            while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                num_str.push(*c);
+                chars.next();
+            }
+            let mut unit = String::new();
+            while let Some(c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                unit.push(*c);
+                chars.next();
+            }
+
+            if num_str.is_empty() || unit.is_empty() {
+                return Err(PafError::create_error(&format!("Invalid duration {}.", s)));
+            }
+            let val: u32 = num_str.parse()?;
+
+            let (field, res) = match unit.as_str() {
+                "y" => (&mut tf.years, Resolution::Year),
+                "mo" => (&mut tf.months, Resolution::Month),
+                "d" => (&mut tf.days, Resolution::Day),
+                "h" => (&mut tf.hours, Resolution::Hour),
+                "m" => (&mut tf.minutes, Resolution::Minute),
+                "s" => (&mut tf.seconds, Resolution::Second),
+                _ => return Err(PafError::create_error(&format!("Unknown unit {} in duration {}.", unit, s)))
+            };
+            *field = field.checked_add(val)
+                .ok_or_else(|| PafError::create_error(&format!("Too large duration {}.", s)))?;
+
+            if res > tf.resolution {
+                tf.resolution = res;
+            }
+        }
+
+        Ok(tf)
+    }
+
+    /// Parses an ISO week token (e.g. `"W14"`) used for week-of-year scheduling, and returns
+    /// the week number. The `W` prefix is case insensitive. Raises an error, if the token is
+    /// malformed, or the week is not between 1 and 53. Weeks can be resolved to dates with
+    /// `DateTime::from_iso_week`.
+    /// 
+    /// ## Arguments
+    /// * `token` - the week token
+    /// 
+    /// ## Examples
+    /// ```
+    /// let week = TimeFreq::parse_iso_week("W14").unwrap();
+    /// assert_eq!(week, 14);
+    /// ```
+    pub fn parse_iso_week(token: &str) -> Result<u32, Box<Error>> {
+        let trimmed = token.trim();
+        let num_str = trimmed.strip_prefix('W').or_else(|| trimmed.strip_prefix('w'))
+            .filter(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+            .ok_or_else(|| PafError::create_error(&format!("Invalid ISO week token {}.", token)))?;
+
+        let week: u32 = num_str.parse()?;
+        if !(1..=53).contains(&week) {
+            return Err(PafError::create_error(&format!("Invalid ISO week {}. It must be between 1 and 53.", week)));
+        }
+        Ok(week)
+    }
+
+    /// Parses a week-of-year pattern, which is an ISO week token optionally followed by a
+    /// complete time of day (e.g. `"W14"` or `"W14 08:30:00"`). Returns `None`, if the pattern
+    /// does not start with a week token, so it can be parsed as a partial timestamp. Raises an
+    /// error, if the week or the time of day is not valid.
+    /// 
+    /// ## Arguments
+    /// * `pattern` - the week pattern
+    /// 
+    /// ## Examples
+    /// ```
+    /// let (week, time) = TimeFreq::parse_iso_week_pattern("W14 08:30:00").unwrap().unwrap();
+    /// assert_eq!(week, 14);
+    /// assert_eq!(time.hours, 8);
+    /// assert!(TimeFreq::parse_iso_week_pattern("08:30:00").unwrap().is_none());
+    /// ```
+    pub fn parse_iso_week_pattern(pattern: &str) -> Result<Option<(u32, TimeFreq)>, Box<Error>> {
+        let trimmed = pattern.trim();
+        if !trimmed.starts_with(&['W', 'w'][..]) {
+            return Ok(None);
+        }
+
+        let (token, time_str) = match trimmed.split_once(char::is_whitespace) {
+            Some((token, rest)) => (token, rest.trim()),
+            None => (trimmed, "")
+        };
+        let week = TimeFreq::parse_iso_week(token)?;
+        if time_str.is_empty() {
+            return Ok(Some((week, TimeFreq {resolution: Resolution::Hour, ..Default::default()})));
+        }
+
+        // The time of day must be complete, as partial times are ambiguous
+        let time = TimeFreq::from_timestamp(time_str, false)?;
+        if time.resolution != Resolution::Hour || time.hours > 23 || time.minutes > 59 || time.seconds > 59 {
+            return Err(PafError::create_error(&format!("Invalid time of day {} in week pattern.", time_str)));
+        }
+        Ok(Some((week, time)))
+    }
+
+    /// Normalizes the components of the `TimeFreq` object according to an overflow policy.
+    /// If excess is carried into a larger component, the resolution is raised accordingly.
+    /// For the available policies, see the `OverflowPolicy` documentation.
+    /// 
+    /// ## Arguments
+    /// * `policy` - the overflow policy
+    /// 
+    /// ## Examples
+    /// ```
+    /// let mut tf = TimeFreq::from_timestamp("90", true).unwrap();
+    /// tf.normalize(&OverflowPolicy::Carry);
+    /// assert_eq!(tf.minutes, 1);
+    /// assert_eq!(tf.seconds, 30);
+    /// ```
+    pub fn normalize(&mut self, policy: &OverflowPolicy) {
+        if let OverflowPolicy::Keep = policy {
+            return;
+        }
+
+        self.minutes = self.minutes.saturating_add(self.seconds / 60);
+        self.seconds %= 60;
+        self.hours = self.hours.saturating_add(self.minutes / 60);
+        self.minutes %= 60;
+        self.days = self.days.saturating_add(self.hours / 24);
+        self.hours %= 24;
+        self.years = self.years.saturating_add(self.months / 12);
+        self.months %= 12;
+
+        // Raise resolution to the largest non-zero component
+        let largest = if self.years > 0 {
+            Resolution::Year
+        } else if self.months > 0 {
+            Resolution::Month
+        } else if self.days > 0 {
+            Resolution::Day
+        } else if self.hours > 0 {
+            Resolution::Hour
+        } else if self.minutes > 0 {
+            Resolution::Minute
+        } else {
+            Resolution::None
+        };
+        if largest > self.resolution {
+            self.resolution = largest;
+        }
+    }
+
+    /// Returns the length of one cycle of the `TimeFreq` object used as a recurring pattern,
+    /// which is one unit of the next larger component than the resolution (e.g. one day for
+    /// `Resolution::Hour`). Returns `None` for `Resolution::Year`, as such patterns do not
+    /// repeat, and a zero length cycle for `Resolution::None`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let tf = TimeFreq::from_timestamp("12:00:00", false).unwrap();
+    /// assert_eq!(tf.cycle_length().unwrap().days, 1);
+    /// ```
+    pub fn cycle_length(&self) -> Option<TimeFreq> {
+        let cycle = match self.resolution {
+            Resolution::Year => return None,
+            Resolution::Month => TimeFreq {years: 1, resolution: Resolution::Year, ..Default::default()},
+            Resolution::Day => TimeFreq {months: 1, resolution: Resolution::Month, ..Default::default()},
+            Resolution::Hour => TimeFreq {days: 1, resolution: Resolution::Day, ..Default::default()},
+            Resolution::Minute => TimeFreq {hours: 1, resolution: Resolution::Hour, ..Default::default()},
+            Resolution::Second => TimeFreq {minutes: 1, resolution: Resolution::Minute, ..Default::default()},
+            Resolution::None => TimeFreq::default()
+        };
+        Some(cycle)
+    }
+
+    /// Returns a canonical string representation of the `TimeFreq` object. Components are
+    /// normalized with `OverflowPolicy::Carry`, and written in the general format
+    /// %Y-%m-%d %H:%M:%S, omitting the components larger than the largest non-zero one.
+    /// Therefore, equivalent frequencies (e.g. `"60:0"`, `"1:0:0"`, and `"0-0 1:0:0"`) have
+    /// identical representations, regardless of how they were written.
+    /// 
+    /// ## Examples
+    /// ```
+    /// let tf = TimeFreq::from_timestamp("60:0", true).unwrap();
+    /// assert_eq!(tf.canonical_string(), "01:00:00");
+    /// ```
+    pub fn canonical_string(&self) -> String {
+        let mut tf = self.clone();
+        tf.normalize(&OverflowPolicy::Carry);
+
+        let time = format!("{:02}:{:02}:{:02}", tf.hours, tf.minutes, tf.seconds);
+        if tf.years > 0 {
+            format!("{}-{:02}-{:02} {}", tf.years, tf.months, tf.days, time)
+        } else if tf.months > 0 {
+            format!("{:02}-{:02} {}", tf.months, tf.days, time)
+        } else if tf.days > 0 {
+            format!("{:02} {}", tf.days, time)
+        } else if tf.hours > 0 {
+            time
+        } else if tf.minutes > 0 {
+            format!("{:02}:{:02}", tf.minutes, tf.seconds)
+        } else {
+            format!("{:02}", tf.seconds)
+        }
+    }
+
+    /// Adds two `TimeFreq` objects component-wise, and normalizes the result according
+    /// to an overflow policy. The resolution of the result is the larger one.
+    /// 
+    /// ## Arguments
+    /// * `other` - the `TimeFreq` object to add
+    /// * `policy` - the overflow policy
+    /// 
+    /// ## Examples
+    /// ```
+    /// let tf = TimeFreq::from_timestamp("45", true).unwrap();
+    /// let sum = tf.add_with_policy(&TimeFreq::from_timestamp("45", true).unwrap(), &OverflowPolicy::Keep);
+    /// assert_eq!(sum.seconds, 90);
+    /// ```
+    pub fn add_with_policy(&self, other: &TimeFreq, policy: &OverflowPolicy) -> TimeFreq {
+        let mut sum = TimeFreq {
+            years: self.years.saturating_add(other.years),
+            months: self.months.saturating_add(other.months),
+            days: self.days.saturating_add(other.days),
+            hours: self.hours.saturating_add(other.hours),
+            minutes: self.minutes.saturating_add(other.minutes),
+            seconds: self.seconds.saturating_add(other.seconds),
+            resolution: if self.resolution >= other.resolution {self.resolution} else {other.resolution}
+        };
+        sum.normalize(policy);
+        sum
+    }
+
+    /// Checks if every component of the `TimeFreq` object is zero. As a concrete time, it
+    /// is valid, but as a frequency, it does not advance time.
+    /// 
+    /// ## Examples
+    /// ```
+    /// assert!(TimeFreq::from_timestamp("0:0:0", true).unwrap().is_zero());
+    /// ```
+    pub fn is_zero(&self) -> bool {
+        self.years == 0 && self.months == 0 && self.calc_duration() == 0
+    }
+
     /// Calculates a duration in seconds from the `TimeFreq` object's
     /// trivially processable components (days, hours, minutes, seconds).
     /// 
@@ -261,10 +658,78 @@ impl TimeFreq {
         secs += self.minutes as i64 * 60;
         secs + self.seconds as i64
     }
+
+    /// Calculates the greatest common interval of several frequencies, i.e. the longest
+    /// interval, which divides every frequency's duration (see `calc_duration`). The result
+    /// is normalized with `OverflowPolicy::Carry`. Returns `None`, if any of the frequencies
+    /// has months or years, as those are not fixed length, or if there is no non-zero duration.
+    /// 
+    /// ## Arguments
+    /// * `freqs` - The frequencies to service with a single interval
+    /// 
+    /// ## Examples
+    /// ```
+    /// let freqs = [TimeFreq::from_timestamp("0:15:0", true).unwrap(), TimeFreq::from_timestamp("0:20:0", true).unwrap()];
+    /// let gcd = TimeFreq::gcd_interval(&freqs).unwrap();
+    /// assert_eq!(gcd.minutes, 5);
+    /// ```
+    pub fn gcd_interval(freqs: &[TimeFreq]) -> Option<TimeFreq> {
+        if freqs.iter().any(|tf| tf.years > 0 || tf.months > 0) {
+            return None;
+        }
+
+        let gcd = freqs.iter().fold(0, |acc, tf| {
+            let (mut a, mut b) = (acc, tf.calc_duration());
+            while b != 0 {
+                let rem = a % b;
+                a = b;
+                b = rem;
+            }
+            a
+        });
+        if gcd == 0 || gcd > u32::MAX as i64 {
+            return None;
+        }
+
+        let mut interval = TimeFreq {seconds: gcd as u32, resolution: Resolution::Second, ..Default::default()};
+        interval.normalize(&OverflowPolicy::Carry);
+        Some(interval)
+    }
+}
+
+/// Adds two `TimeFreq` objects with the default `OverflowPolicy::Carry` policy.
+/// For other policies, use `TimeFreq::add_with_policy`.
+impl Add for TimeFreq {
+    type Output = TimeFreq;
+
+    fn add(self, other: TimeFreq) -> TimeFreq {
+        self.add_with_policy(&other, &Default::default())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    mod resolution {
+        use super::super::*;
+
+        #[test]
+        fn compares_resolutions() {
+            assert!(Resolution::Second.finer_than(&Resolution::Hour));
+            assert!(!Resolution::Hour.finer_than(&Resolution::Second));
+            assert!(Resolution::Year.coarser_than(&Resolution::Month));
+            assert!(!Resolution::Day.coarser_than(&Resolution::Day));
+            assert!(!Resolution::Day.finer_than(&Resolution::Day));
+        }
+
+        #[test]
+        fn lists_from_coarsest_to_finest() {
+            let all = Resolution::all();
+            assert_eq!(all[0], Resolution::Year);
+            assert_eq!(all[6], Resolution::None);
+            assert!(all.windows(2).all(|pair| pair[0].coarser_than(&pair[1])));
+        }
+    }
+
     mod sanitize_timestr_arr {
         use super::super::*;
 
@@ -283,6 +748,17 @@ mod tests {
         }
     }
 
+    mod _join_separators {
+        use super::super::*;
+
+        #[test]
+        fn removes_whitespaces_around_separators() {
+            assert_eq!(TimeFreq::_join_separators(" 1 : 0 : 0 "), "1:0:0");
+            assert_eq!(TimeFreq::_join_separators("1 - 2 -3  4: 5:6"), "1-2-3 4:5:6");
+            assert_eq!(TimeFreq::_join_separators("\t1-1-1   1:1:1\n"), "1-1-1 1:1:1");
+        }
+    }
+
     mod parse_timestamp {
         use super::super::*;
 
@@ -320,6 +796,15 @@ mod tests {
     mod from_timestamp {
         use super::super::*;
 
+        #[test]
+        fn limits_trimmed_length() {
+            let padded = format!("{}1:2:3{}", " ".repeat(MAX_TIMESTAMP_LEN), "\n".repeat(MAX_TIMESTAMP_LEN));
+            assert_eq!(TimeFreq::from_timestamp(&padded, true).unwrap().hours, 1);
+
+            let long = format!("1:2:{}3", "0".repeat(MAX_TIMESTAMP_LEN));
+            assert!(TimeFreq::from_timestamp(&long, true).is_err());
+        }
+
         #[test]
         fn parses_full_timestamp() {
             let timestamp = "1-2-3 4:5:6";
@@ -450,6 +935,22 @@ mod tests {
             assert!(ts_obj.is_err());
         }
 
+        #[test]
+        fn throws_error_on_adversarial_input() {
+            let long_digits = "1".repeat(10_000);
+            let long_separators = ":".repeat(10_000);
+            let inputs = [
+                ":::", "-", "--", "- :", ": -", "1-2-3-4", "1:2:3:4", "+5", "1 2 3",
+                "\u{0}", "\u{663}:\u{664}", "1:\u{1F550}", "\u{FF11}:00", "4294967296", "99999999999:0",
+                "1-2 3", &long_digits, &long_separators
+            ];
+
+            for input in inputs.iter() {
+                assert!(TimeFreq::from_timestamp(input, true).is_err(), "accepted {:?}", input);
+                assert!(TimeFreq::from_timestamp(input, false).is_err(), "accepted {:?}", input);
+            }
+        }
+
         #[test]
         fn tolerates_bad_formatting() {
             let ts_obj = TimeFreq::from_timestamp("   1-2-3  \n  4:5:6  \t", true);
@@ -467,6 +968,21 @@ mod tests {
             assert_eq!(6, ts_obj.months);
         }
 
+        #[test]
+        fn wraps_large_month_counts_exactly() {
+            let mut ts_obj = TimeFreq::from_timestamp("1000-0", true).unwrap();
+            assert_eq!(83, ts_obj.years);
+            assert_eq!(4, ts_obj.months);
+
+            ts_obj = TimeFreq::from_timestamp("16777217-0", true).unwrap();
+            assert_eq!(1398101, ts_obj.years);
+            assert_eq!(5, ts_obj.months);
+
+            ts_obj = TimeFreq::from_timestamp("4294967295-0", true).unwrap();
+            assert_eq!(357913941, ts_obj.years);
+            assert_eq!(3, ts_obj.months);
+        }
+
         #[test]
         fn does_not_wrap_years_if_not_requested() {
             let mut ts_obj = TimeFreq::from_timestamp("12-0", false).unwrap();
@@ -488,4 +1004,346 @@ mod tests {
 
         }
     }
+
+    mod is_zero {
+        use super::super::*;
+
+        #[test]
+        fn detects_zero() {
+            assert!(TimeFreq::from_timestamp("0:0:0", true).unwrap().is_zero());
+            assert!(TimeFreq::from_timestamp("0", true).unwrap().is_zero());
+            assert!(TimeFreq::from_timestamp("0-0-0 0:0:0", true).unwrap().is_zero());
+        }
+
+        #[test]
+        fn detects_non_zero() {
+            assert!(!TimeFreq::from_timestamp("0:0:1", true).unwrap().is_zero());
+            assert!(!TimeFreq::from_timestamp("1-0 0:0:0", true).unwrap().is_zero());
+            assert!(!TimeFreq::from_timestamp("1-0-0 0:0:0", true).unwrap().is_zero());
+        }
+    }
+
+    mod from_timestamp_strict {
+        use super::super::*;
+
+        #[test]
+        fn rejects_zero_frequency() {
+            let err = TimeFreq::from_timestamp_strict("0:0:0", true).err().unwrap();
+            assert!(err.to_string().contains("zero frequency"));
+            assert!(TimeFreq::from_timestamp_strict("0", true).is_err());
+        }
+
+        #[test]
+        fn accepts_non_zero_frequency() {
+            assert_eq!(TimeFreq::from_timestamp_strict("0:15:0", true).unwrap().minutes, 15);
+        }
+
+        #[test]
+        fn keeps_lenient_parsing() {
+            assert!(TimeFreq::from_timestamp("0:0:0", true).is_ok());
+        }
+    }
+
+    mod parse_patterns {
+        use super::super::*;
+
+        #[test]
+        fn parses_list() {
+            let patterns = TimeFreq::parse_patterns("08:00:00;12:00:00").unwrap();
+            assert_eq!(patterns.len(), 2);
+            assert_eq!((patterns[0].hours, patterns[1].hours), (8, 12));
+
+            let patterns = TimeFreq::parse_patterns(" 1-15 12:00:00 , 2019-01-01 00:00:00 ").unwrap();
+            assert_eq!(patterns.len(), 2);
+            assert_eq!((patterns[0].months, patterns[0].days), (1, 15));
+            assert_eq!(patterns[1].years, 2019);
+        }
+
+        #[test]
+        fn parses_single_pattern() {
+            let patterns = TimeFreq::parse_patterns("30:00").unwrap();
+            assert_eq!(patterns.len(), 1);
+            assert_eq!(patterns[0].minutes, 30);
+        }
+
+        #[test]
+        fn reports_index_of_invalid_pattern() {
+            let err = TimeFreq::parse_patterns("08:00:00;1:2:3:4;12:00:00").err().unwrap();
+            assert!(err.to_string().contains("index 1"));
+
+            let err = TimeFreq::parse_patterns("08:00:00;").err().unwrap();
+            assert!(err.to_string().contains("index 1"));
+        }
+    }
+
+    mod from_shorthand {
+        use super::super::*;
+
+        #[test]
+        fn parses_hours_and_minutes() {
+            let ts_obj = TimeFreq::from_shorthand("1h30m").unwrap();
+            assert_eq!(1, ts_obj.hours);
+            assert_eq!(30, ts_obj.minutes);
+            assert_eq!(0, ts_obj.seconds);
+            assert!(ts_obj.resolution == Resolution::Hour);
+        }
+
+        #[test]
+        fn parses_days() {
+            let ts_obj = TimeFreq::from_shorthand("2d").unwrap();
+            assert_eq!(2, ts_obj.days);
+            assert_eq!(0, ts_obj.hours);
+            assert!(ts_obj.resolution == Resolution::Day);
+        }
+
+        #[test]
+        fn distinguishes_months_from_minutes() {
+            let ts_obj = TimeFreq::from_shorthand("1mo15d").unwrap();
+            assert_eq!(1, ts_obj.months);
+            assert_eq!(15, ts_obj.days);
+            assert_eq!(0, ts_obj.minutes);
+            assert!(ts_obj.resolution == Resolution::Month);
+        }
+
+        #[test]
+        fn accepts_any_order() {
+            let ts_obj = TimeFreq::from_shorthand("30s 1y 5m").unwrap();
+            assert_eq!(1, ts_obj.years);
+            assert_eq!(5, ts_obj.minutes);
+            assert_eq!(30, ts_obj.seconds);
+            assert!(ts_obj.resolution == Resolution::Year);
+        }
+
+        #[test]
+        fn throws_error_on_invalid_input() {
+            assert!(TimeFreq::from_shorthand("5x").is_err());
+            assert!(TimeFreq::from_shorthand("").is_err());
+            assert!(TimeFreq::from_shorthand("h").is_err());
+            assert!(TimeFreq::from_shorthand("15").is_err());
+        }
+    }
+
+    mod normalize {
+        use super::super::*;
+
+        #[test]
+        fn keeps_overflow() {
+            let mut ts_obj = TimeFreq::from_timestamp("90", true).unwrap();
+            ts_obj.normalize(&OverflowPolicy::Keep);
+            assert_eq!(0, ts_obj.minutes);
+            assert_eq!(90, ts_obj.seconds);
+            assert!(ts_obj.resolution == Resolution::Second);
+        }
+
+        #[test]
+        fn carries_overflow() {
+            let mut ts_obj = TimeFreq::from_timestamp("90", true).unwrap();
+            ts_obj.normalize(&OverflowPolicy::Carry);
+            assert_eq!(1, ts_obj.minutes);
+            assert_eq!(30, ts_obj.seconds);
+            assert!(ts_obj.resolution == Resolution::Minute);
+        }
+
+        #[test]
+        fn carries_through_every_component() {
+            let mut ts_obj = TimeFreq::from_timestamp("0-13-40 23:59:60", false).unwrap();
+            ts_obj.normalize(&OverflowPolicy::Carry);
+            assert_eq!(1, ts_obj.years);
+            assert_eq!(1, ts_obj.months);
+            assert_eq!(41, ts_obj.days);
+            assert_eq!(0, ts_obj.hours);
+            assert_eq!(0, ts_obj.minutes);
+            assert_eq!(0, ts_obj.seconds);
+        }
+    }
+
+    mod cycle_length {
+        use super::super::*;
+
+        #[test]
+        fn returns_next_unit() {
+            let mut cycle = TimeFreq::from_timestamp("02-11 10:30:00", false).unwrap().cycle_length().unwrap();
+            assert_eq!((cycle.years, cycle.months, cycle.calc_duration()), (1, 0, 0));
+            assert!(cycle.resolution == Resolution::Year);
+
+            cycle = TimeFreq::from_timestamp("11 10:30:00", false).unwrap().cycle_length().unwrap();
+            assert_eq!((cycle.years, cycle.months, cycle.calc_duration()), (0, 1, 0));
+            assert!(cycle.resolution == Resolution::Month);
+
+            cycle = TimeFreq::from_timestamp("10:30:00", false).unwrap().cycle_length().unwrap();
+            assert_eq!((cycle.years, cycle.months, cycle.calc_duration()), (0, 0, 86_400));
+            assert!(cycle.resolution == Resolution::Day);
+
+            cycle = TimeFreq::from_timestamp("30:00", false).unwrap().cycle_length().unwrap();
+            assert_eq!((cycle.years, cycle.months, cycle.calc_duration()), (0, 0, 3_600));
+            assert!(cycle.resolution == Resolution::Hour);
+
+            cycle = TimeFreq::from_timestamp("30", false).unwrap().cycle_length().unwrap();
+            assert_eq!((cycle.years, cycle.months, cycle.calc_duration()), (0, 0, 60));
+            assert!(cycle.resolution == Resolution::Minute);
+        }
+
+        #[test]
+        fn returns_zero_without_resolution() {
+            let cycle = TimeFreq::default().cycle_length().unwrap();
+            assert_eq!((cycle.years, cycle.months, cycle.calc_duration()), (0, 0, 0));
+        }
+
+        #[test]
+        fn returns_none_for_years() {
+            let tf = TimeFreq::from_timestamp("2019-02-11 10:30:00", false).unwrap();
+            assert!(tf.cycle_length().is_none());
+        }
+    }
+
+    mod canonical_string {
+        use super::super::*;
+
+        #[test]
+        fn canonicalizes_equivalent_frequencies() {
+            let hour = TimeFreq::from_timestamp("1:0:0", true).unwrap();
+            let minutes = TimeFreq::from_timestamp("60:0", true).unwrap();
+            assert_eq!(hour.canonical_string(), minutes.canonical_string());
+            assert_eq!(hour.canonical_string(), "01:00:00");
+
+            let secs = TimeFreq::from_timestamp("90", true).unwrap();
+            let mins = TimeFreq::from_timestamp("1:30", true).unwrap();
+            assert_eq!(secs.canonical_string(), mins.canonical_string());
+            assert_eq!(secs.canonical_string(), "01:30");
+        }
+
+        #[test]
+        fn writes_dates() {
+            let mut tf = TimeFreq::from_timestamp("1-2-3 4:5:6", true).unwrap();
+            assert_eq!(tf.canonical_string(), "1-02-03 04:05:06");
+
+            tf = TimeFreq::from_timestamp("48:0:0", true).unwrap();
+            assert_eq!(tf.canonical_string(), "02 00:00:00");
+        }
+
+        #[test]
+        fn round_trips() {
+            let tf = TimeFreq::from_timestamp("2-35 0:0:0", false).unwrap();
+            let canonical = tf.canonical_string();
+            let parsed = TimeFreq::from_timestamp(&canonical, false).unwrap();
+            assert_eq!(parsed.canonical_string(), canonical);
+        }
+
+        #[test]
+        fn ignores_input_resolution() {
+            let secs = TimeFreq::from_timestamp("30", true).unwrap();
+            let mins = TimeFreq::from_timestamp("0:30", true).unwrap();
+            let hours = TimeFreq::from_timestamp("00:00:30", true).unwrap();
+            let days = TimeFreq::from_timestamp("0-0 0:0:30", true).unwrap();
+            assert_eq!(secs.canonical_string(), "30");
+            assert_eq!(mins.canonical_string(), "30");
+            assert_eq!(hours.canonical_string(), "30");
+            assert_eq!(days.canonical_string(), "30");
+        }
+
+        #[test]
+        fn accepts_whitespace_around_separators() {
+            let spaced = TimeFreq::from_timestamp(" 1 : 0 : 0 ", true).unwrap();
+            let minutes = TimeFreq::from_timestamp("60:0", true).unwrap();
+            assert_eq!(spaced.canonical_string(), minutes.canonical_string());
+        }
+    }
+
+    mod parse_iso_week {
+        use super::super::*;
+
+        #[test]
+        fn parses_week_token() {
+            assert_eq!(TimeFreq::parse_iso_week("W14").unwrap(), 14);
+            assert_eq!(TimeFreq::parse_iso_week(" w01 ").unwrap(), 1);
+            assert_eq!(TimeFreq::parse_iso_week("W53").unwrap(), 53);
+        }
+
+        #[test]
+        fn throws_error_on_invalid_token() {
+            assert!(TimeFreq::parse_iso_week("W0").is_err());
+            assert!(TimeFreq::parse_iso_week("W54").is_err());
+            assert!(TimeFreq::parse_iso_week("14").is_err());
+            assert!(TimeFreq::parse_iso_week("W").is_err());
+            assert!(TimeFreq::parse_iso_week("W1a").is_err());
+        }
+    }
+
+    mod parse_iso_week_pattern {
+        use super::super::*;
+
+        #[test]
+        fn parses_week_with_time_of_day() {
+            let (week, time) = TimeFreq::parse_iso_week_pattern("W14 08:30:15").unwrap().unwrap();
+            assert_eq!(week, 14);
+            assert_eq!((time.hours, time.minutes, time.seconds), (8, 30, 15));
+
+            let (week, time) = TimeFreq::parse_iso_week_pattern(" w2 ").unwrap().unwrap();
+            assert_eq!(week, 2);
+            assert!(time.is_zero());
+        }
+
+        #[test]
+        fn ignores_other_patterns() {
+            assert!(TimeFreq::parse_iso_week_pattern("08:30:00").unwrap().is_none());
+            assert!(TimeFreq::parse_iso_week_pattern("1-1 0:0:0").unwrap().is_none());
+        }
+
+        #[test]
+        fn throws_error_on_invalid_pattern() {
+            assert!(TimeFreq::parse_iso_week_pattern("W54").is_err());
+            assert!(TimeFreq::parse_iso_week_pattern("W14 30:00").is_err());
+            assert!(TimeFreq::parse_iso_week_pattern("W14 24:00:00").is_err());
+            assert!(TimeFreq::parse_iso_week_pattern("W14 1-1 0:0:0").is_err());
+        }
+    }
+
+    mod gcd_interval {
+        use super::super::*;
+
+        #[test]
+        fn returns_common_interval() {
+            let freqs = [TimeFreq::from_timestamp("0:15:0", true).unwrap(), TimeFreq::from_timestamp("0:20:0", true).unwrap()];
+            let gcd = TimeFreq::gcd_interval(&freqs).unwrap();
+            assert_eq!((gcd.hours, gcd.minutes, gcd.seconds), (0, 5, 0));
+            assert!(gcd.resolution == Resolution::Minute);
+        }
+
+        #[test]
+        fn returns_none_with_months() {
+            let freqs = [TimeFreq::from_timestamp("0:15:0", true).unwrap(), TimeFreq::from_timestamp("1-0 0:0:0", true).unwrap()];
+            assert!(TimeFreq::gcd_interval(&freqs).is_none());
+        }
+
+        #[test]
+        fn returns_none_without_duration() {
+            assert!(TimeFreq::gcd_interval(&[]).is_none());
+            assert!(TimeFreq::gcd_interval(&[TimeFreq::default()]).is_none());
+        }
+    }
+
+    mod add {
+        use super::super::*;
+
+        #[test]
+        fn carries_by_default() {
+            let sum = TimeFreq::from_timestamp("45", true).unwrap() + TimeFreq::from_timestamp("45", true).unwrap();
+            assert_eq!(1, sum.minutes);
+            assert_eq!(30, sum.seconds);
+        }
+
+        #[test]
+        fn keeps_if_requested() {
+            let tf = TimeFreq::from_timestamp("45", true).unwrap();
+            let sum = tf.add_with_policy(&TimeFreq::from_timestamp("45", true).unwrap(), &OverflowPolicy::Keep);
+            assert_eq!(0, sum.minutes);
+            assert_eq!(90, sum.seconds);
+        }
+
+        #[test]
+        fn uses_larger_resolution() {
+            let sum = TimeFreq::from_timestamp("1:0:0", true).unwrap() + TimeFreq::from_timestamp("30", true).unwrap();
+            assert!(sum.resolution == Resolution::Hour);
+        }
+    }
 }